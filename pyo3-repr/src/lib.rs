@@ -102,7 +102,8 @@ fn gen_named_impl(ident: String, fields: &FieldsNamed) -> MacroResult {
         let field_name_ident = f.ident.as_ref().unwrap();
         let field_name_literal = field_name_ident.to_string();
         let mut py_convert = is_py_ptr(&f.ty);
-        for attr in f.attrs.iter() {
+        let mut skip = false;
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("py_debug")) {
             attr.parse_nested_meta(|meta| {
                 let ident = meta.path.get_ident().ok_or_else(|| {
                     syn::Error::new_spanned(
@@ -111,7 +112,9 @@ fn gen_named_impl(ident: String, fields: &FieldsNamed) -> MacroResult {
                     )
                 })?;
                 match ident.to_string().as_str() {
-                    "skip" => return Ok(()),
+                    "skip" => {
+                        skip = true;
+                    }
                     "py" => {
                         py_convert = true;
                     }
@@ -123,6 +126,9 @@ fn gen_named_impl(ident: String, fields: &FieldsNamed) -> MacroResult {
                 Ok(())
             })?;
         }
+        if skip {
+            continue;
+        }
         if py_convert {
             core_stream.extend(quote!(
                 .field(#field_name_literal, self.#field_name_ident.as_ref(py))
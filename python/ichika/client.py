@@ -20,6 +20,7 @@ from .message.elements import (
     Image,
     MusicShare,
     Reply,
+    SuperFace,
     Text,
 )
 
@@ -127,7 +128,7 @@ class Client(PlumbingClient):
     def _validate_chain(chain: MessageChain) -> MessageChain | Element:
         if not chain:
             raise ValueError("无法发送空消息！")
-        if any(not isinstance(elem, (Reply, At, AtAll, Text, Image, Face)) for elem in chain):
+        if any(not isinstance(elem, (Reply, At, AtAll, Text, Image, Face, SuperFace)) for elem in chain):
             if len(chain) > 1:
                 raise ValueError("消息内混合了富文本和非富文本型消息！")
             elem = chain[0]
@@ -193,12 +194,17 @@ class Client(PlumbingClient):
         raise TypeError(f"无法发送元素: {element!r}")
 
     async def send_group_message(
-        self, group: int | Group, chain: str | Element | MessageChain | Iterable[str | Element]
+        self,
+        group: int | Group,
+        chain: str | Element | MessageChain | Iterable[str | Element],
+        *,
+        resolve_at: bool = False,
     ) -> RawMessageReceipt:
         """发送群消息
 
         :param group: 群号或群对象
         :param chain: 消息链
+        :param resolve_at: 是否为未指定显示名的 `At` 元素自动获取群名片或昵称
 
         :return: 消息发送凭据，可用于撤回
         """
@@ -208,7 +214,7 @@ class Client(PlumbingClient):
             return await self._send_special_element(uin, "group", validated)
         for idx, elem in enumerate(chain):
             chain.content[idx] = await self._validate_mm(uin, elem, self.upload_group_image)
-        receipt = await super().send_group_message(uin, _serialize_msg(chain))
+        receipt = await super().send_group_message(uin, _serialize_msg(chain), resolve_at=resolve_at)
         if receipt.seq == 0:
             raise MessageSendFailed(f"failed on group {uin}, unexcepted zero seq")
         return receipt
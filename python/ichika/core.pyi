@@ -1,8 +1,12 @@
+from asyncio import AbstractEventLoop
 from dataclasses import dataclass
-from datetime import datetime
-from typing import Awaitable, Literal, Protocol, Sequence, TypeVar, type_check_only
+from datetime import datetime, timedelta
+from os import PathLike
+from typing import Awaitable, Callable, Iterable, Literal, Protocol, Sequence, TypeVar, type_check_only
 from typing_extensions import Any, TypeAlias
 
+from graia.amnesia.message import MessageChain
+
 from ichika.message.elements import MusicShare
 from ichika.structs import Gender, GroupPermission
 
@@ -13,7 +17,7 @@ from .login import (
     PasswordLoginCallbacks,
     QRCodeLoginCallbacks,
 )
-from .message._sealed import SealedAudio
+from .message._sealed import SealedAudio, SealedMarketFace
 
 __version__: str
 __build__: Any
@@ -27,6 +31,113 @@ class EventCallback(Protocol):
     async def put(self, event: _T_Event, /) -> Any:
         """处理事件"""
 
+class EventFilter:
+    """事件过滤器，在 Rust 侧求值，所有条件均满足时事件才会投递给对应的队列。
+
+    群与用户条件只作用于带有对应信息的事件，文本条件会排除所有非消息事件。
+    """
+
+    def __init__(
+        self,
+        *,
+        types: Iterable[str] | None = None,
+        groups: Iterable[int] | None = None,
+        exclude_groups: Iterable[int] | None = None,
+        users: Iterable[int] | None = None,
+        exclude_users: Iterable[int] | None = None,
+        ignore_self: bool = False,
+        ignore_client_sent: bool = False,
+        prefix: str | None = None,
+        pattern: str | None = None,
+    ) -> None:
+        """
+        :param types: 允许的事件类型名，如 `GroupMessage`
+        :param groups: 允许的群号
+        :param exclude_groups: 排除的群号
+        :param users: 允许的用户 QQ 号
+        :param exclude_users: 排除的用户 QQ 号
+        :param ignore_self: 是否忽略机器人自身触发的事件，包括其他设备发送的消息
+        :param ignore_client_sent: 是否忽略由当前客户端发送的消息同步事件
+        :param prefix: 消息纯文本需要带有的前缀
+        :param pattern: 消息纯文本需要匹配的正则表达式，使用 Rust `regex` 语法
+        """
+
+class DispatchPolicy:
+    """有序投递策略。
+
+    使用此策略的队列由独立的任务按顺序调用 `put`，同一会话（群或用户）内的事件投递顺序与接收顺序一致。
+    多个队列共用同一个策略对象时，统计数据也会合并计算。
+    """
+
+    def __init__(
+        self,
+        *,
+        capacity: int = 256,
+        overflow: Literal["block", "drop_oldest", "drop_newest"] = "block",
+        timeout: float | None = None,
+        workers: int = 1,
+    ) -> None:
+        """
+        :param capacity: 每个投递任务的缓冲区大小
+        :param overflow: 缓冲区已满时的处理方式，`block` 会阻塞事件接收直到缓冲区有空位
+        :param timeout: 单次 `put` 的超时秒数
+        :param workers: 投递任务数，不同会话的事件可以在不同任务中并行投递
+        """
+    @property
+    def dropped(self) -> int:
+        """因缓冲区已满而被丢弃的事件数"""
+    @property
+    def timed_out(self) -> int:
+        """投递超时的事件数"""
+
+class FailedDelivery:
+    """投递失败的事件，交给登录时传入的死信队列"""
+
+    stage: Literal["conversion", "client_lookup", "put", "timeout"]
+    """失败的阶段"""
+    type_name: str
+    """事件类型名，转换前失败时为 RICQ 事件名"""
+    event: Any
+    """已转换的事件对象，转换完成前失败时为 `None`"""
+    event_repr: str
+    """事件的字符串表示"""
+    error: BaseException
+    """引发的异常"""
+
+EventConsumer: TypeAlias = EventCallback | Callable[[Any], Any]
+"""事件消费者，可以是带有 `put` 方法的队列，也可以是线程安全的同步可调用对象。
+
+`put` 为协程函数的队列（包括 `asyncio.Queue`）在事件循环上运行；
+同步的 `put` 方法（如 `queue.Queue`）与同步可调用对象会在阻塞线程池中被调用，不会阻塞事件接收。
+"""
+
+EventSubscriber: TypeAlias = (
+    EventConsumer
+    | tuple[EventConsumer, EventFilter]
+    | tuple[EventConsumer, DispatchPolicy]
+    | tuple[EventConsumer, EventFilter, DispatchPolicy]
+    | tuple[EventCallback, AbstractEventLoop]
+    | tuple[EventCallback, EventFilter, AbstractEventLoop]
+    | tuple[EventCallback, DispatchPolicy, AbstractEventLoop]
+    | tuple[EventCallback, EventFilter, DispatchPolicy, AbstractEventLoop]
+)
+"""事件队列，可以附带 [`EventFilter`][ichika.core.EventFilter]、[`DispatchPolicy`][ichika.core.DispatchPolicy] 与事件循环。
+
+指定事件循环时，队列的 `put` 协程会在该事件循环上运行，适用于在其他线程中运行的消费者；否则使用登录时的事件循环。
+"""
+
+class Subscription:
+    """事件队列的订阅
+
+    由 [`subscribe`][ichika.core.PlumbingClient.subscribe] 返回，登录时传入的事件队列的订阅可以通过
+    [`subscriptions`][ichika.core.PlumbingClient.subscriptions] 获取。
+    """
+
+    uin: int
+    """所属账号的 QQ 号"""
+    id: int
+    """订阅编号"""
+
 # Here, outside wrapper "login_XXX" ensures that a "task locals" can be acquired for event task execution.
 
 async def password_login(
@@ -35,8 +146,13 @@ async def password_login(
     use_sms: bool,
     protocol: str,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[EventCallback],
+    event_callbacks: Sequence[EventSubscriber],
     login_callbacks: PasswordLoginCallbacks,
+    *,
+    dedup_window: float | None = 60.0,
+    record: str | PathLike[str] | None = None,
+    archive: str | PathLike[str] | None = None,
+    dead_letter: EventCallback | None = None,
 ) -> Client:
     """使用密码登录。
 
@@ -47,6 +163,10 @@ async def password_login(
     :param store: 登录凭据存储器
     :param event_callbacks: 事件队列
     :param login_callbacks: 用于解析登录的回调
+    :param dedup_window: 重复事件的过滤时间窗口秒数，为 `None` 时不过滤
+    :param record: 事件记录文件路径，接收到的事件会以 JSON Lines 格式追加写入，可以通过 `replay_events` 回放
+    :param archive: 消息存档文件路径，接收到的与发送的群消息和好友消息会写入该 SQLite 数据库，可以通过 `MessageArchive` 查询
+    :param dead_letter: 死信队列，投递失败的事件会以 `FailedDelivery` 的形式交给它，为 `None` 时只打印异常
     :return: 可操作的客户端
     """
 
@@ -54,8 +174,13 @@ async def qrcode_login(
     uin: int,
     protocol: str,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[EventCallback],
+    event_callbacks: Sequence[EventSubscriber],
     login_callbacks: QRCodeLoginCallbacks,
+    *,
+    dedup_window: float | None = 60.0,
+    record: str | PathLike[str] | None = None,
+    archive: str | PathLike[str] | None = None,
+    dead_letter: EventCallback | None = None,
 ) -> Client:
     """使用二维码登录。
 
@@ -64,9 +189,28 @@ async def qrcode_login(
     :param store: 登录凭据存储器
     :param event_callbacks: 事件队列
     :param login_callbacks: 用于解析登录的回调
+    :param dedup_window: 重复事件的过滤时间窗口秒数，为 `None` 时不过滤
+    :param record: 事件记录文件路径，接收到的事件会以 JSON Lines 格式追加写入，可以通过 `replay_events` 回放
+    :param archive: 消息存档文件路径，接收到的与发送的群消息和好友消息会写入该 SQLite 数据库，可以通过 `MessageArchive` 查询
+    :param dead_letter: 死信队列，投递失败的事件会以 `FailedDelivery` 的形式交给它，为 `None` 时只打印异常
     :return: 可操作的客户端
     """
 
+async def replay_events(
+    path: str | PathLike[str],
+    queues: Sequence[EventSubscriber],
+    client: Client | None = None,
+) -> int:
+    """按记录顺序将事件记录文件中的事件投递给事件队列，无需网络连接与登录。
+
+    每个事件会等待所有队列的 `put` 完成后再投递下一个，事件过滤器同样生效。
+
+    :param path: 事件记录文件路径
+    :param queues: 事件队列
+    :param client: 设置到事件 `client` 属性的客户端
+    :return: 投递的事件数
+    """
+
 # region: client
 
 _internal_repr = dataclass(frozen=True, init=False)
@@ -324,11 +468,79 @@ class PlumbingClient:
     @property
     def online(self) -> bool:
         """当前账号是否登录成功。"""
+    def subscribe(self, consumer: EventSubscriber, filter: EventFilter | None = None) -> Subscription:
+        """在登录后添加事件队列。
+
+        :param consumer: 事件队列，与登录时传入的事件队列格式相同
+        :param filter: 事件过滤器，会替换 `consumer` 中附带的过滤器
+        :return: 用于取消订阅的订阅对象
+        """
+    @property
+    def subscriptions(self) -> list[Subscription]:
+        """当前有效的订阅，按添加顺序排列，登录时传入的事件队列在最前。"""
+    def unsubscribe(self, subscription: Subscription) -> bool:
+        """取消订阅，之后的事件不会再投递给对应的事件队列。
+
+        使用 [`DispatchPolicy`][ichika.core.DispatchPolicy] 的队列的投递任务会被停止，缓冲区中尚未投递的事件将被丢弃。
+
+        :param subscription: [`subscribe`][ichika.core.PlumbingClient.subscribe] 返回的订阅对象，或从 [`subscriptions`][ichika.core.PlumbingClient.subscriptions] 获取的订阅对象
+        :return: 订阅是否仍然有效
+        """
+    def wait_for(
+        self, filter: EventFilter, timeout: float | None = None, consume: bool = False
+    ) -> Awaitable[event_defs.Event]:
+        """等待下一个通过过滤器的事件，适用于需要多轮对话的场景。
+
+        过滤器在 Rust 侧求值，可以按会话（群号）、发送者、事件类型与消息文本筛选事件。
+        多个等待者按注册顺序依次求值，每个等待者只接收一个事件。
+
+        :param filter: 事件过滤器
+        :param timeout: 超时秒数，None 为不超时
+        :param consume: 是否消费该事件，消费后之后的等待者与事件队列都不会再收到该事件
+        :raises TimeoutError: 超时时抛出
+        :return: 第一个通过过滤器的事件
+        """
+    def get_message(
+        self,
+        source: MessageSource | RawMessageReceipt,
+        kind: Literal["group", "friend"] | None = None,
+        target: int | None = None,
+    ) -> CachedMessage | None:
+        """从最近消息的缓存中获取消息。
+
+        缓存包含最近接收到的与当前客户端发送的群消息和好友消息，可以用于构造带有原消息内容的 `Reply` 元素。
+        消息按会话区分，使用 `MessageSource` 时需要指定 `kind` 与 `target`，回执自带会话信息。
+
+        :param source: 消息元信息或发送消息的回执
+        :param kind: 会话类型，`source` 为回执时可省略
+        :param target: 群号或好友 QQ 号，`source` 为回执时可省略
+        :return: 缓存的消息，不在缓存中时为 `None`
+        """
+    @property
+    def archive(self) -> MessageArchive | None:
+        """登录时指定的消息存档，未指定时为 `None`。"""
+    def delivery_errors(self) -> dict[str, int]:
+        """各事件类型的投递失败次数。
+
+        :return: 事件类型名到失败次数的映射
+        """
     def keep_alive(self) -> Awaitable[None]:
         """保持在线。
 
         :return: 承载了维持心跳和重连任务的 [`Future 对象`][asyncio.Future]。
         """
+    def watch_roster(self, interval: float = 300.0, group_intervals: dict[int, float] | None = None) -> Awaitable[None]:
+        """定期获取好友、群与群成员列表，并将与上次快照的差异作为事件投递。
+
+        会产生 [`MemberCardChange`][ichika.core.MemberCardChange]、[`MemberTitleChange`][ichika.core.MemberTitleChange]、
+        [`GroupMemoChange`][ichika.core.GroupMemoChange]、[`FriendRemarkChange`][ichika.core.FriendRemarkChange]、
+        [`OfflineMemberJoin`][ichika.core.OfflineMemberJoin] 与 [`OfflineMemberLeave`][ichika.core.OfflineMemberLeave] 事件。
+        首次获取的列表只作为快照，客户端离线时暂停获取。
+
+        :param interval: 获取间隔的秒数，也是群成员列表的默认获取间隔
+        :param group_intervals: 各群成员列表的获取间隔，不大于 0 时不获取该群的成员列表
+        :return: 承载了获取任务的 [`Future 对象`][asyncio.Future]，取消即可停止
+        """
     async def stop(self) -> None:
         """停止客户端运行。
 
@@ -401,6 +613,14 @@ class PlumbingClient:
         :param uin: 好友 QQ 号
         :return: 好友对象，如果不存在则返回 None
         """
+    async def get_friend(self, uin: int) -> Friend | None:
+        """获取单个好友。
+
+        协议没有查询单个好友的接口，优先从好友列表的短期缓存中查找（`watch_roster` 运行时使用其最近获取的列表），未找到时获取完整的好友列表。
+
+        :param uin: 好友 QQ 号
+        :return: 好友对象，如果不是好友则返回 None
+        """
     async def nudge_friend(self, uin: int) -> None:
         """给好友发送窗口抖动。
 
@@ -412,12 +632,13 @@ class PlumbingClient:
         :param uin: 好友 QQ 号
         """
     # [impl 3]
-    async def get_group(self, uin: int, cache: bool = True) -> Group:
-        """获取群信息。
+    async def get_group(self, uin: int) -> Group | None:
+        """获取单个群的最新信息。
+
+        与 `get_groups` 不同，返回的群信息包含最后一条消息的序列号 `last_msg_seq`。
 
         :param uin: 群号
-        :param cache: 是否使用缓存
-        :return: 群信息
+        :return: 群信息，如果不在群中则返回 None
         """
     async def find_group(self, uin: int) -> Group | None:
         """查找群。
@@ -460,13 +681,13 @@ class PlumbingClient:
         :param uin: 群号
         """
     # [impl 4]
-    async def get_member(self, group_uin: int, uin: int, cache: bool = False) -> Member:
-        """获取群成员信息。
+    async def get_member(self, group_uin: int, uin: int) -> Member:
+        """获取单个群成员信息，无需获取整个成员列表。
 
         :param group_uin: 群号
         :param uin: QQ 号
-        :param cache: 是否使用缓存
         :return: 群成员信息
+        :raises ValueError: 不是群成员时抛出
         """
     async def get_member_list(self, group_uin: int, cache: bool = True) -> list[Member]:
         """获取群成员列表。
@@ -561,6 +782,16 @@ class PlumbingClient:
         :param audio: 语音数据
         :return: 发送结果
         """
+    async def get_audio_url(self, audio: SealedAudio, kind: Literal["group", "friend"], target: int) -> str:
+        """获取语音的下载链接。
+
+        语音事件不会预先获取链接，其中语音元素的 `url` 为空字符串。
+
+        :param audio: 语音数据
+        :param kind: 语音来源类型
+        :param target: 群号或发送语音的好友 QQ 号
+        :return: 语音下载链接
+        """
     async def send_friend_music_share(self, uin: int, share: MusicShare) -> RawMessageReceipt:
         """发送好友音乐分享。
 
@@ -589,6 +820,13 @@ class PlumbingClient:
         :param msg: 转发消息
         :return: 上传结果
         """
+    async def fetch_market_face_pack(self, downloader: HttpClientProto, tab_id: int) -> MarketFacePack:
+        """获取商城表情包中可用的表情。
+
+        :param downloader: 下载器
+        :param tab_id: 表情包 ID
+        :return: 表情包信息
+        """
     # [impl 6]
     async def send_friend_message(self, uin: int, chain: list[dict[str, Any]]) -> RawMessageReceipt:
         """发送好友消息。
@@ -597,11 +835,14 @@ class PlumbingClient:
         :param chain: 消息链
         :return: 发送结果
         """
-    async def send_group_message(self, uin: int, chain: list[dict[str, Any]]) -> RawMessageReceipt:
+    async def send_group_message(
+        self, uin: int, chain: list[dict[str, Any]], *, resolve_at: bool = False
+    ) -> RawMessageReceipt:
         """发送群消息。
 
         :param uin: QQ 号
         :param chain: 消息链
+        :param resolve_at: 是否为未指定显示名的 `At` 元素自动获取群名片或昵称，结果会被短暂缓存
         :return: 发送结果
         """
     async def recall_friend_message(self, uin: int, time: int, seq: int, rand: int) -> None:
@@ -661,8 +902,192 @@ class PlumbingClient:
 
 # endregion: client
 
-def face_id_from_name(name: str) -> int | None: ...
-def face_name_from_id(id: int) -> str: ...
+def face_id_from_name(name: str) -> int | None:
+    """通过名称或别名获取表情索引，支持 `/微笑`、`/wx` 形式。
+
+    :param name: 表情名称或别名
+    :return: 表情索引，未知表情返回 None
+    """
+
+def face_name_from_id(id: int) -> str:
+    """通过索引获取表情名称。
+
+    :param id: 表情索引
+    :return: 表情名称
+    """
+
+@_internal_repr
+class MarketFaceItem:
+    """商城表情包中的单个表情"""
+
+    face_id: str
+    """表情 ID，为十六进制字符串"""
+    name: str
+    """表情名称"""
+    tab_id: int
+    """所属表情包 ID"""
+    width: int
+    """表情宽度"""
+    height: int
+    """表情高度"""
+
+@_internal_repr
+class MarketFacePack:
+    """商城表情包"""
+
+    tab_id: int
+    """表情包 ID"""
+    name: str
+    """表情包名称"""
+    faces: VTuple[MarketFaceItem]
+    """表情包中的表情"""
+
+@_internal_repr
+class FaceInfo:
+    """表情目录中的表情信息"""
+
+    index: int
+    """表情索引"""
+    name: str
+    """表情名称"""
+    aliases: VTuple[str]
+    """表情别名"""
+    is_super: bool
+    """是否为新版小表情，发送时使用超级表情协议"""
+    is_animated: bool
+    """是否拥有大号动画形式"""
+    sticker_type: int | None
+    """大号动画形式的贴纸类型"""
+
+def face_catalogue() -> list[FaceInfo]:
+    """获取完整的表情目录。
+
+    :return: 表情信息列表
+    """
+
+def face_info(index: int) -> FaceInfo | None:
+    """通过索引获取表情信息。
+
+    :param index: 表情索引
+    :return: 表情信息，未收录的表情返回 None
+    """
+
+def face_info_from_name(name: str) -> FaceInfo | None:
+    """通过名称或别名获取表情信息，支持 `/微笑`、`/wx` 形式。
+
+    :param name: 表情名称或别名
+    :return: 表情信息，未收录的表情返回 None
+    """
+
+@_internal_repr
+class DecodedQRCode:
+    """识别出的二维码"""
+
+    content: str
+    """二维码内容"""
+    bounds: tuple[tuple[int, int], tuple[int, int], tuple[int, int], tuple[int, int]]
+    """二维码四角在图像中的坐标"""
+    version: int
+    """二维码版本"""
+    ecc_level: int
+    """纠错等级"""
+
+def decode_qrcodes(data: bytes) -> list[DecodedQRCode]:
+    """识别图像中的所有二维码。
+
+    无法解码的二维码会被跳过，发现了二维码但全部无法解码时抛出 `ValueError`。
+
+    :param data: 图像数据
+    :return: 识别出的二维码列表
+    """
+
+def make_qrcode_image(text: str, scale: int = 8, ecc: Literal["L", "M", "Q", "H"] = "M") -> bytes:
+    """生成二维码图像。
+
+    返回的数据可以直接用于 [`upload_group_image`][ichika.client.Client.upload_group_image]。
+
+    :param text: 二维码内容
+    :param scale: 每个模块占用的像素数
+    :param ecc: 纠错等级
+    :return: PNG 格式的图像数据
+    :raises ValueError: 缩放倍数为 0 或图像边长超过 8192 像素时抛出
+    """
+
+def render_transcript(
+    messages: list[dict | GroupMessage | FriendMessage | CachedMessage | tuple[int, datetime | int, MessageChain]],
+    format: Literal["html", "markdown", "text", "json"],
+    *,
+    title: str = "聊天记录",
+    images: dict[str, bytes] = {},
+) -> str:
+    """将消息渲染为聊天记录。
+
+    :param messages: 消息列表，元素可以是与 `upload_forward_msg` 相同格式的转发消息字典、消息事件、`CachedMessage` 或 `(发送者 QQ 号, 发送时间, 消息链)` 三元组
+    :param format: 输出格式，`json` 格式可以被 `load_transcript` 重新读取
+    :param title: 聊天记录标题
+    :param images: 图片链接到图片数据的映射，存在的图片会以 base64 形式内嵌，其余图片以链接引用
+    :return: 渲染结果
+    """
+
+def load_transcript(path: str | PathLike[str]) -> list[dict]:
+    """读取 JSON 格式的聊天记录。
+
+    :param path: 文件路径
+    :return: 与 `download_forward_msg` 相同格式的转发消息列表
+    """
+
+def elements_to_cq(elements: list[dict]) -> str:
+    """将元素字典列表转换为 CQ 码字符串。
+
+    :param elements: 元素字典列表
+    :return: CQ 码字符串
+    """
+
+def cq_to_elements(text: str) -> list[dict]:
+    """将 CQ 码字符串转换为元素字典列表。
+
+    无法识别的 CQ 码会被转换为 `Unknown` 元素字典，CQ 码类型保存在 `cq_type` 中，原始参数保存在 `params` 中。
+
+    :param text: CQ 码字符串
+    :return: 元素字典列表
+    """
+
+def elements_to_json(elements: list[dict]) -> str:
+    """将元素字典列表序列化为带版本号的 JSON。
+
+    :param elements: 元素字典列表
+    :return: JSON 字符串
+    """
+
+def elements_from_json(data: str) -> list[dict]:
+    """从 JSON 反序列化元素字典列表。
+
+    :param data: JSON 字符串
+    :return: 元素字典列表
+    """
+
+def event_to_json(event: dict | event_defs.Event) -> str:
+    """将事件序列化为带版本号的 JSON，`content` 字段需为元素字典列表。
+
+    :param event: 事件对象或事件字典
+    :return: JSON 字符串
+    """
+
+def event_from_json(data: str, client: Client | None = None) -> event_defs.Event:
+    """从 JSON 反序列化事件。
+
+    :param data: JSON 字符串
+    :param client: 设置到事件 `client` 属性的客户端
+    :return: 事件
+    """
+
+def wire_schema(kind: Literal["chain", "event"]) -> str:
+    """获取传输格式的 JSON Schema。
+
+    :param kind: `chain` 为消息链，`event` 为事件
+    :return: JSON Schema 字符串
+    """
+
 @_internal_repr
 class MessageSource:
     """消息元信息"""
@@ -683,3 +1108,540 @@ class MessageSource:
 
     time: datetime
     """消息发送时间"""
+
+@_internal_repr
+class MessageSender:
+    """消息携带的发送者信息"""
+
+    uin: int
+    """发送者 QQ 号"""
+    nickname: str | None
+    """昵称，群消息不携带"""
+    card_name: str | None
+    """群名片，仅群消息携带，未设置时为空字符串"""
+    role: GroupPermission | None
+    """群成员权限，仅在通过 `get_member_list` 获取过该成员时可用"""
+    @property
+    def name(self) -> str:
+        """显示名称，依次使用群名片、昵称与 QQ 号"""
+
+@_internal_repr
+class CachedMessage:
+    """最近消息缓存中的消息"""
+
+    kind: Literal["group", "friend"]
+    """消息类型"""
+    target: int
+    """群号或好友 QQ 号"""
+    sender: int
+    """发送者 QQ 号"""
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+
+class MessageArchive:
+    """消息的本地存档
+
+    存档为 SQLite 数据库，多个账号可以共用同一个存档文件。
+    消息内容以带版本号的 JSON 消息链保存，格式与 `elements_to_json` 相同。
+    """
+
+    @staticmethod
+    def open(path: str | PathLike[str]) -> MessageArchive:
+        """打开存档文件，用于在不登录的情况下查询存档。
+
+        :param path: 存档文件路径，不存在时会被创建
+        :return: 消息存档
+        """
+    async def query(
+        self,
+        *,
+        uin: int | None = None,
+        kind: Literal["group", "friend"] | None = None,
+        target: int | None = None,
+        sender: int | None = None,
+        start: datetime | int | None = None,
+        end: datetime | int | None = None,
+        text: str | None = None,
+        limit: int = 100,
+        newest_first: bool = False,
+    ) -> list[CachedMessage]:
+        """查询存档的消息，为 `None` 的条件不参与筛选。
+
+        :param uin: 存档消息的账号
+        :param kind: 消息类型
+        :param target: 群号或好友 QQ 号，与 `kind` 一起使用以确定会话
+        :param sender: 发送者 QQ 号
+        :param start: 起始时间（包含）
+        :param end: 结束时间（不包含）
+        :param text: 在纯文本内容中搜索的文本
+        :param limit: 最多返回的消息数
+        :param newest_first: 是否按时间从新到旧排列，默认从旧到新
+        :return: 符合条件的消息
+        """
+
+@type_check_only
+class _BaseEvent:
+    client: Client
+    """事件所属的 [`Client`][ichika.client.Client] 对象"""
+    def __getitem__(self, key: str) -> Any:
+        """按字段名获取属性，用于兼容旧版的字典事件"""
+
+@_internal_repr
+class GroupMessage(_BaseEvent):
+    """群消息"""
+    __match_args__ = ("source", "content", "group", "sender", "group_name", "sender_info")
+    type_name: Literal["GroupMessage"]
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+    group: int
+    """群号"""
+    sender: int
+    """发送者 QQ 号"""
+    group_name: str
+    """群名称"""
+    sender_info: MessageSender
+    """发送者信息"""
+
+@_internal_repr
+class GroupRecallMessage(_BaseEvent):
+    """群消息撤回"""
+    __match_args__ = ("time", "group", "author", "operator", "seq", "content")
+    type_name: Literal["GroupRecallMessage"]
+    time: datetime
+    """撤回时间"""
+    group: int
+    """群号"""
+    author: int
+    """消息发送者 QQ 号"""
+    operator: int
+    """撤回者 QQ 号"""
+    seq: int
+    """被撤回消息的 SEQ"""
+    content: MessageChain | None
+    """被撤回消息的内容，仅在原消息仍在最近消息缓存中时可用"""
+
+@_internal_repr
+class FriendMessage(_BaseEvent):
+    """好友消息"""
+    __match_args__ = ("source", "content", "sender", "sender_info")
+    type_name: Literal["FriendMessage"]
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+    sender: int
+    """发送者 QQ 号"""
+    sender_info: MessageSender
+    """发送者信息"""
+
+@_internal_repr
+class FriendRecallMessage(_BaseEvent):
+    """好友消息撤回"""
+    __match_args__ = ("time", "author", "seq", "content")
+    type_name: Literal["FriendRecallMessage"]
+    time: datetime
+    """撤回时间"""
+    author: int
+    """消息发送者 QQ 号"""
+    seq: int
+    """被撤回消息的 SEQ"""
+    content: MessageChain | None
+    """被撤回消息的内容，仅在原消息仍在最近消息缓存中时可用"""
+
+@_internal_repr
+class TempMessage(_BaseEvent):
+    """群临时会话消息"""
+    __match_args__ = ("source", "content", "group", "sender", "sender_info")
+    type_name: Literal["TempMessage"]
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+    group: int
+    """群号"""
+    sender: int
+    """发送者 QQ 号"""
+    sender_info: MessageSender
+    """发送者信息"""
+
+@_internal_repr
+class SelfGroupMessage(_BaseEvent):
+    """本账号发送的群消息，包括其他设备发送后同步的消息"""
+    __match_args__ = ("source", "content", "group", "sender", "group_name", "sender_info", "from_client")
+    type_name: Literal["SelfGroupMessage"]
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+    group: int
+    """群号"""
+    sender: int
+    """本账号 QQ 号"""
+    group_name: str
+    """群名称"""
+    sender_info: MessageSender
+    """发送者信息"""
+    from_client: bool
+    """是否由当前客户端发送"""
+
+@_internal_repr
+class SelfFriendMessage(_BaseEvent):
+    """本账号发送的好友消息，包括其他设备发送后同步的消息"""
+    __match_args__ = ("source", "content", "sender", "target", "sender_info", "from_client")
+    type_name: Literal["SelfFriendMessage"]
+    source: MessageSource
+    """消息元信息"""
+    content: MessageChain
+    """消息内容"""
+    sender: int
+    """本账号 QQ 号"""
+    target: int
+    """接收消息的好友 QQ 号"""
+    sender_info: MessageSender
+    """发送者信息"""
+    from_client: bool
+    """是否由当前客户端发送"""
+
+@_internal_repr
+class GroupNudge(_BaseEvent):
+    """群戳一戳"""
+    __match_args__ = ("group", "sender", "receiver")
+    type_name: Literal["GroupNudge"]
+    group: int
+    """群号"""
+    sender: int
+    """发送者 QQ 号"""
+    receiver: int
+    """接收者 QQ 号"""
+
+@_internal_repr
+class FriendNudge(_BaseEvent):
+    """好友戳一戳"""
+    __match_args__ = ("sender",)
+    type_name: Literal["FriendNudge"]
+    sender: int
+    """发送者 QQ 号"""
+
+@_internal_repr
+class NewFriend(_BaseEvent):
+    """新好友"""
+    __match_args__ = ("friend",)
+    type_name: Literal["NewFriend"]
+    friend: Friend
+    """好友信息"""
+
+@_internal_repr
+class NewMember(_BaseEvent):
+    """新群成员"""
+    __match_args__ = ("group", "member")
+    type_name: Literal["NewMember"]
+    group: int
+    """群号"""
+    member: int
+    """新成员 QQ 号"""
+
+@_internal_repr
+class MemberLeaveGroup(_BaseEvent):
+    """群成员离开"""
+    __match_args__ = ("group_uin", "member_uin", "operator_uin", "kind")
+    type_name: Literal["MemberLeaveGroup"]
+    group_uin: int
+    """群号"""
+    member_uin: int
+    """成员 QQ 号"""
+    operator_uin: int | None
+    """移出成员的操作者 QQ 号，主动退群时为 `None`"""
+    kind: Literal["quit", "kicked"]
+    """离开方式"""
+
+@_internal_repr
+class BotJoinGroup(_BaseEvent):
+    """机器人加入群"""
+    __match_args__ = ("group_uin",)
+    type_name: Literal["BotJoinGroup"]
+    group_uin: int
+    """群号"""
+
+@_internal_repr
+class BotLeaveGroup(_BaseEvent):
+    """机器人主动退群"""
+    __match_args__ = ("group_uin",)
+    type_name: Literal["BotLeaveGroup"]
+    group_uin: int
+    """群号"""
+
+@_internal_repr
+class BotKicked(_BaseEvent):
+    """机器人被移出群"""
+    __match_args__ = ("group_uin", "operator_uin")
+    type_name: Literal["BotKicked"]
+    group_uin: int
+    """群号"""
+    operator_uin: int
+    """操作者 QQ 号"""
+
+@_internal_repr
+class GroupDisband(_BaseEvent):
+    """群解散"""
+    __match_args__ = ("group_uin", "operator_uin")
+    type_name: Literal["GroupDisband"]
+    group_uin: int
+    """群号"""
+    operator_uin: int
+    """操作者 QQ 号"""
+
+@_internal_repr
+class FriendDeleted(_BaseEvent):
+    """好友被删除"""
+    __match_args__ = ("friend_uin",)
+    type_name: Literal["FriendDeleted"]
+    friend_uin: int
+    """好友 QQ 号"""
+
+@_internal_repr
+class GroupMute(_BaseEvent):
+    """全体禁言状态变更"""
+    __match_args__ = ("group", "operator", "status")
+    type_name: Literal["GroupMute"]
+    group: int
+    """群号"""
+    operator: int
+    """操作者 QQ 号"""
+    status: bool
+    """是否开启全体禁言"""
+
+@_internal_repr
+class MemberMute(_BaseEvent):
+    """群成员禁言"""
+    __match_args__ = ("group", "operator", "target", "duration")
+    type_name: Literal["MemberMute"]
+    group: int
+    """群号"""
+    operator: int
+    """操作者 QQ 号"""
+    target: int
+    """被禁言者 QQ 号"""
+    duration: timedelta | Literal[False]
+    """禁言时长，为 `False` 时表示解除禁言"""
+
+@_internal_repr
+class MemberPermissionChange(_BaseEvent):
+    """群成员权限变更"""
+    __match_args__ = ("group", "target", "permission")
+    type_name: Literal["MemberPermissionChange"]
+    group: int
+    """群号"""
+    target: int
+    """成员 QQ 号"""
+    permission: int
+    """新权限"""
+
+@_internal_repr
+class GroupInfoUpdate(_BaseEvent):
+    """群信息变更"""
+    __match_args__ = ("group", "operator", "info")
+    type_name: Literal["GroupInfoUpdate"]
+    group: int
+    """群号"""
+    operator: int
+    """操作者 QQ 号"""
+    info: dict[str, Any]
+    """变更的群信息，目前只包含 `name`"""
+
+@_internal_repr
+class MemberCardChange(_BaseEvent):
+    """群名片变更，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("group", "member", "old", "new")
+    type_name: Literal["MemberCardChange"]
+    group: int
+    """群号"""
+    member: int
+    """成员 QQ 号"""
+    old: str
+    """原群名片"""
+    new: str
+    """新群名片"""
+
+@_internal_repr
+class MemberTitleChange(_BaseEvent):
+    """群专属头衔变更，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("group", "member", "old", "new")
+    type_name: Literal["MemberTitleChange"]
+    group: int
+    """群号"""
+    member: int
+    """成员 QQ 号"""
+    old: str
+    """原专属头衔"""
+    new: str
+    """新专属头衔"""
+
+@_internal_repr
+class GroupMemoChange(_BaseEvent):
+    """群公告变更，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("group", "old", "new")
+    type_name: Literal["GroupMemoChange"]
+    group: int
+    """群号"""
+    old: str
+    """原群公告"""
+    new: str
+    """新群公告"""
+
+@_internal_repr
+class FriendRemarkChange(_BaseEvent):
+    """好友备注变更，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("friend_uin", "old", "new")
+    type_name: Literal["FriendRemarkChange"]
+    friend_uin: int
+    """好友 QQ 号"""
+    old: str
+    """原备注"""
+    new: str
+    """新备注"""
+
+@_internal_repr
+class OfflineMemberJoin(_BaseEvent):
+    """离线期间加入群的成员，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("group", "member")
+    type_name: Literal["OfflineMemberJoin"]
+    group: int
+    """群号"""
+    member: int
+    """成员 QQ 号"""
+
+@_internal_repr
+class OfflineMemberLeave(_BaseEvent):
+    """离线期间离开群的成员，由 [`watch_roster`][ichika.core.PlumbingClient.watch_roster] 比较列表快照得出"""
+    __match_args__ = ("group", "member")
+    type_name: Literal["OfflineMemberLeave"]
+    group: int
+    """群号"""
+    member: int
+    """成员 QQ 号"""
+
+@_internal_repr
+class NewFriendRequest(_BaseEvent):
+    """好友申请"""
+    __match_args__ = ("seq", "uin", "nickname", "message")
+    type_name: Literal["NewFriendRequest"]
+    seq: int
+    """申请 SEQ"""
+    uin: int
+    """申请者 QQ 号"""
+    nickname: str
+    """申请者昵称"""
+    message: str
+    """验证消息"""
+
+@_internal_repr
+class JoinGroupRequest(_BaseEvent):
+    """入群申请"""
+    __match_args__ = ("seq", "time", "group_uin", "group_name", "request_uin", "request_nickname", "suspicious", "invitor_uin", "invitor_nickname")
+    type_name: Literal["JoinGroupRequest"]
+    seq: int
+    """申请 SEQ"""
+    time: datetime
+    """申请时间"""
+    group_uin: int
+    """群号"""
+    group_name: str
+    """群名"""
+    request_uin: int
+    """申请者 QQ 号"""
+    request_nickname: str
+    """申请者昵称"""
+    suspicious: bool
+    """是否为可疑账号"""
+    invitor_uin: int | None
+    """邀请者 QQ 号"""
+    invitor_nickname: str | None
+    """邀请者昵称"""
+
+@_internal_repr
+class JoinGroupInvitation(_BaseEvent):
+    """入群邀请"""
+    __match_args__ = ("seq", "time", "group_uin", "group_name", "invitor_uin", "invitor_nickname")
+    type_name: Literal["JoinGroupInvitation"]
+    seq: int
+    """邀请 SEQ"""
+    time: datetime
+    """邀请时间"""
+    group_uin: int
+    """群号"""
+    group_name: str
+    """群名"""
+    invitor_uin: int
+    """邀请者 QQ 号"""
+    invitor_nickname: str
+    """邀请者昵称"""
+
+@_internal_repr
+class UnknownEvent(_BaseEvent):
+    """未知事件"""
+    __match_args__ = ("internal_repr",)
+    type_name: Literal["UnknownEvent"]
+    internal_repr: str
+    """事件的内部表示"""
+
+@_internal_repr
+class Connected(_BaseEvent):
+    """客户端登录成功"""
+    __match_args__ = ()
+    type_name: Literal["Connected"]
+
+@_internal_repr
+class Disconnected(_BaseEvent):
+    """客户端断开连接"""
+    __match_args__ = ("reason",)
+    type_name: Literal["Disconnected"]
+    reason: Literal["network", "network_offline", "kicked_offline", "msf_offline", "dropped", "stopped", "unknown"]
+    """断开原因"""
+
+@_internal_repr
+class Reconnecting(_BaseEvent):
+    """客户端重连失败，正在等待下次重连"""
+    __match_args__ = ("attempt", "delay")
+    type_name: Literal["Reconnecting"]
+    attempt: int
+    """已失败的重连次数"""
+    delay: timedelta
+    """距离下次重连的时间"""
+
+@_internal_repr
+class Reconnected(_BaseEvent):
+    """客户端重连成功"""
+    __match_args__ = ()
+    type_name: Literal["Reconnected"]
+
+@_internal_repr
+class LoggedOut(_BaseEvent):
+    """客户端已下线且不会再重连"""
+    __match_args__ = ("error",)
+    type_name: Literal["LoggedOut"]
+    error: str | None
+    """重连失败的原因，不是因为重连失败而下线时为 `None`"""
+
+@_internal_repr
+class KickedOffline(_BaseEvent):
+    """被其他设备登录挤下线"""
+    __match_args__ = ("title", "tips")
+    type_name: Literal["KickedOffline"]
+    title: str
+    """提示标题"""
+    tips: str
+    """提示内容"""
+
+@_internal_repr
+class MSFOffline(_BaseEvent):
+    """被服务器强制下线"""
+    __match_args__ = ("title", "info")
+    type_name: Literal["MSFOffline"]
+    title: str
+    """提示标题"""
+    info: str
+    """提示内容"""
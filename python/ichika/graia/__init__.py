@@ -3,6 +3,7 @@ from __future__ import annotations
 import asyncio
 from contextvars import ContextVar
 from functools import partial
+from os import PathLike
 from typing import Any, Awaitable, Literal, Optional, Protocol, Set
 from typing_extensions import Literal, Self
 
@@ -52,9 +53,9 @@ class BroadcastCallback:
     async def put(self, data: Any) -> None:
         from .event import EVENT_TYPES
 
-        client = data.pop("client")
+        client = data.client
 
-        e = EVENT_TYPES[data.pop("type_name")](**data)
+        e = EVENT_TYPES[data.type_name](**{name: getattr(data, name) for name in data.__match_args__})
         client_token = CLIENT_INSTANCE.set(client)
         event_token = BROADCAST_EVENT.set(e)
         await self.broadcast.postEvent(e)
@@ -84,6 +85,7 @@ class IchikaComponent(Launchable):
         self.store: BaseLoginCredentialStore = store
         self.login_partials: dict[int, IchikaComponent._LoginPartial] = {}
         self.client_hb_map: dict[int, tuple[Client, Awaitable[None]]] = {}
+        self.subscriptions: dict[int, list[core.Subscription]] = {}
         super().__init__()
 
     id = "ichika.main"
@@ -104,6 +106,10 @@ class IchikaComponent(Launchable):
         protocol: PasswordProtocol = "AndroidPad",
         callbacks: PasswordLoginCallbacks | None = None,
         use_sms: bool = True,
+        dedup_window: float | None = 60.0,
+        record: str | PathLike[str] | None = None,
+        archive: str | PathLike[str] | None = None,
+        dead_letter: core.EventCallback | None = None,
     ) -> Self:
         if uin in self.login_partials:
             raise ValueError(f"账号 {uin} 已经存在")
@@ -114,6 +120,10 @@ class IchikaComponent(Launchable):
             protocol=protocol,
             login_callbacks=callbacks,
             use_sms=use_sms,
+            dedup_window=dedup_window,
+            record=record,
+            archive=archive,
+            dead_letter=dead_letter,
         )
         return self
 
@@ -123,10 +133,23 @@ class IchikaComponent(Launchable):
         /,
         protocol: Literal["AndroidWatch"] = "AndroidWatch",
         callbacks: QRCodeLoginCallbacks | None = None,
+        dedup_window: float | None = 60.0,
+        record: str | PathLike[str] | None = None,
+        archive: str | PathLike[str] | None = None,
+        dead_letter: core.EventCallback | None = None,
     ) -> Self:
         if uin in self.login_partials:
             raise ValueError(f"账号 {uin} 已经存在")
-        self.login_partials[uin] = partial(login_qrcode, uin, protocol=protocol, login_callbacks=callbacks)
+        self.login_partials[uin] = partial(
+            login_qrcode,
+            uin,
+            protocol=protocol,
+            login_callbacks=callbacks,
+            dedup_window=dedup_window,
+            record=record,
+            archive=archive,
+            dead_letter=dead_letter,
+        )
         return self
 
     async def launch(self, mgr: Launart):
@@ -144,6 +167,7 @@ class IchikaComponent(Launchable):
                     if not client.online:
                         raise LoginError(f"账号 {uin} 被服务器断开连接。")
                     self.client_hb_map[uin] = (client, client.keep_alive())
+                    self.subscriptions[uin] = client.subscriptions
                 except Exception as e:
                     logger.exception(f"账号 {uin} 登录失败: ", e)
             if not self.client_hb_map:
@@ -151,8 +175,10 @@ class IchikaComponent(Launchable):
 
         async with self.stage("blocking"):
             await mgr.status.wait_for_sigexit()
-            # 清空事件回调
-            event_cbs.clear()  # LINK: https://github.com/BlueGlassBlock/Ichika/issues/61
+            # 取消登录时添加的事件回调
+            for uin, (client, _) in self.client_hb_map.items():
+                for subscription in self.subscriptions.pop(uin, []):
+                    client.unsubscribe(subscription)
             logger.info("事件监听已终止。")
 
         async with self.stage("cleanup"):
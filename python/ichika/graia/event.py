@@ -9,7 +9,7 @@ from graia.broadcast.entities.event import BaseDispatcher, Dispatchable
 from graia.broadcast.entities.signatures import Force
 from graia.broadcast.interfaces.dispatcher import DispatcherInterface as DI
 
-from ichika.core import Friend, Group, Member, MessageSource
+from ichika.core import Friend, Group, Member, MessageSender, MessageSource
 from ichika.utils import generic_issubclass
 
 
@@ -122,6 +122,8 @@ class GroupMessage(MessageEvent, GroupEvent):
     content: MessageChain
     group: Group
     sender: Member
+    group_name: str
+    sender_info: MessageSender
 
 
 @dataclass
@@ -130,6 +132,7 @@ class FriendMessage(MessageEvent):
     source: MessageSource
     content: MessageChain
     sender: Friend
+    sender_info: MessageSender
 
 
 @dataclass
@@ -139,6 +142,30 @@ class TempMessage(MessageEvent):
     content: MessageChain
     group: Group
     sender: Member
+    sender_info: MessageSender
+
+
+@dataclass
+@auto_dispatch
+class SelfGroupMessage(MessageEvent, GroupEvent):
+    source: MessageSource
+    content: MessageChain
+    group: Group
+    sender: Member
+    group_name: str
+    sender_info: MessageSender
+    from_client: bool
+
+
+@dataclass
+@auto_dispatch
+class SelfFriendMessage(MessageEvent):
+    source: MessageSource
+    content: MessageChain
+    sender: int
+    target: Friend
+    sender_info: MessageSender
+    from_client: bool
 
 
 @dataclass
@@ -149,6 +176,7 @@ class GroupRecallMessage(Dispatchable):
     author: Member
     operator: Member
     seq: int
+    content: Optional[MessageChain]
 
 
 @dataclass
@@ -157,6 +185,7 @@ class FriendRecallMessage(Dispatchable):
     time: datetime
     author: Friend
     seq: int
+    content: Optional[MessageChain]
 
 
 @dataclass
@@ -191,6 +220,27 @@ class NewMember(Dispatchable):
 class MemberLeaveGroup(Dispatchable):
     group_uin: int
     member_uin: int
+    operator_uin: Optional[int]
+    kind: Literal["quit", "kicked"]
+
+
+@dataclass
+@auto_dispatch
+class BotJoinGroup(Dispatchable):
+    group_uin: int
+
+
+@dataclass
+@auto_dispatch
+class BotLeaveGroup(Dispatchable):
+    group_uin: int
+
+
+@dataclass
+@auto_dispatch
+class BotKicked(Dispatchable):
+    group_uin: int
+    operator_uin: int
 
 
 @dataclass
@@ -242,6 +292,54 @@ class GroupInfoUpdate(Dispatchable):
     info: _GroupInfo
 
 
+@dataclass
+@auto_dispatch
+class MemberCardChange(Dispatchable):
+    group: Group
+    member: Member
+    old: str
+    new: str
+
+
+@dataclass
+@auto_dispatch
+class MemberTitleChange(Dispatchable):
+    group: Group
+    member: Member
+    old: str
+    new: str
+
+
+@dataclass
+@auto_dispatch
+class GroupMemoChange(Dispatchable):
+    group: Group
+    old: str
+    new: str
+
+
+@dataclass
+@auto_dispatch
+class FriendRemarkChange(Dispatchable):
+    friend_uin: int
+    old: str
+    new: str
+
+
+@dataclass
+@auto_dispatch
+class OfflineMemberJoin(Dispatchable):
+    group: Group
+    member: Member
+
+
+@dataclass
+@auto_dispatch
+class OfflineMemberLeave(Dispatchable):
+    group: Group
+    member: Member
+
+
 @dataclass
 @auto_dispatch
 class NewFriendRequest(Dispatchable):
@@ -282,6 +380,51 @@ class UnknownEvent(Dispatchable):
     internal_repr: str
 
 
+@dataclass
+@auto_dispatch
+class Connected(Dispatchable):
+    pass
+
+
+@dataclass
+@auto_dispatch
+class Disconnected(Dispatchable):
+    reason: str
+
+
+@dataclass
+@auto_dispatch
+class Reconnecting(Dispatchable):
+    attempt: int
+    delay: timedelta
+
+
+@dataclass
+@auto_dispatch
+class Reconnected(Dispatchable):
+    pass
+
+
+@dataclass
+@auto_dispatch
+class LoggedOut(Dispatchable):
+    error: Optional[str]
+
+
+@dataclass
+@auto_dispatch
+class KickedOffline(Dispatchable):
+    title: str
+    tips: str
+
+
+@dataclass
+@auto_dispatch
+class MSFOffline(Dispatchable):
+    title: str
+    info: str
+
+
 EVENT_TYPES = {
     cls.__name__: cls
     for cls in (
@@ -290,20 +433,38 @@ EVENT_TYPES = {
         FriendMessage,
         FriendRecallMessage,
         TempMessage,
+        SelfGroupMessage,
+        SelfFriendMessage,
         GroupNudge,
         FriendNudge,
         NewFriend,
         NewMember,
         MemberLeaveGroup,
+        BotJoinGroup,
+        BotLeaveGroup,
+        BotKicked,
         GroupDisband,
         FriendDeleted,
         GroupMute,
         MemberMute,
         MemberPermissionChange,
         GroupInfoUpdate,
+        MemberCardChange,
+        MemberTitleChange,
+        GroupMemoChange,
+        FriendRemarkChange,
+        OfflineMemberJoin,
+        OfflineMemberLeave,
         NewFriendRequest,
         JoinGroupRequest,
         JoinGroupInvitation,
         UnknownEvent,
+        Connected,
+        Disconnected,
+        Reconnecting,
+        Reconnected,
+        LoggedOut,
+        KickedOffline,
+        MSFOffline,
     )
 }
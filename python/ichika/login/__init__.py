@@ -4,6 +4,7 @@ import json
 import os
 from asyncio import Queue
 from enum import Enum
+from os import PathLike
 from pathlib import Path
 from typing import Literal, Optional, Sequence, Union, overload
 
@@ -97,9 +98,13 @@ async def login_password(
     /,
     protocol: PasswordProtocol,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[_core.EventCallback],
+    event_callbacks: Sequence[_core.EventSubscriber],
     login_callbacks: PasswordLoginCallbacks | None = None,
     use_sms: bool = ...,
+    dedup_window: float | None = ...,
+    record: str | PathLike[str] | None = ...,
+    archive: str | PathLike[str] | None = ...,
+    dead_letter: _core.EventCallback | None = ...,
 ) -> Client:
     ...
 
@@ -111,9 +116,13 @@ async def login_password(
     /,
     protocol: PasswordProtocol,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[_core.EventCallback],
+    event_callbacks: Sequence[_core.EventSubscriber],
     login_callbacks: PasswordLoginCallbacks | None = None,
     use_sms: bool = ...,
+    dedup_window: float | None = ...,
+    record: str | PathLike[str] | None = ...,
+    archive: str | PathLike[str] | None = ...,
+    dead_letter: _core.EventCallback | None = ...,
 ) -> Client:
     ...
 
@@ -125,9 +134,13 @@ async def login_password(
     /,
     protocol: PasswordProtocol,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[_core.EventCallback],
+    event_callbacks: Sequence[_core.EventSubscriber],
     login_callbacks: PasswordLoginCallbacks | None = None,
     use_sms: bool = ...,
+    dedup_window: float | None = ...,
+    record: str | PathLike[str] | None = ...,
+    archive: str | PathLike[str] | None = ...,
+    dead_letter: _core.EventCallback | None = ...,
 ) -> Client:
     ...
 
@@ -138,12 +151,26 @@ async def login_password(
     /,
     protocol: PasswordProtocol,
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[_core.EventCallback],
+    event_callbacks: Sequence[_core.EventSubscriber],
     login_callbacks: PasswordLoginCallbacks | None = None,
     use_sms: bool = True,
+    dedup_window: float | None = 60.0,
+    record: str | PathLike[str] | None = None,
+    archive: str | PathLike[str] | None = None,
+    dead_letter: _core.EventCallback | None = None,
 ) -> Client:
     return await _core.password_login(
-        uin, credential, use_sms, protocol, store, event_callbacks, login_callbacks or PasswordLoginCallbacks.default()
+        uin,
+        credential,
+        use_sms,
+        protocol,
+        store,
+        event_callbacks,
+        login_callbacks or PasswordLoginCallbacks.default(),
+        dedup_window=dedup_window,
+        record=record,
+        archive=archive,
+        dead_letter=dead_letter,
     )
 
 
@@ -152,9 +179,21 @@ async def login_qrcode(
     /,
     protocol: Literal["AndroidWatch"],
     store: BaseLoginCredentialStore,
-    event_callbacks: Sequence[_core.EventCallback],
+    event_callbacks: Sequence[_core.EventSubscriber],
     login_callbacks: QRCodeLoginCallbacks | None = None,
+    dedup_window: float | None = 60.0,
+    record: str | PathLike[str] | None = None,
+    archive: str | PathLike[str] | None = None,
+    dead_letter: _core.EventCallback | None = None,
 ) -> Client:
     return await _core.qrcode_login(
-        uin, protocol, store, event_callbacks, login_callbacks or QRCodeLoginCallbacks.default()
+        uin,
+        protocol,
+        store,
+        event_callbacks,
+        login_callbacks or QRCodeLoginCallbacks.default(),
+        dedup_window=dedup_window,
+        record=record,
+        archive=archive,
+        dead_letter=dead_letter,
     )
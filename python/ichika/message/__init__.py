@@ -1,21 +1,24 @@
 from __future__ import annotations
 
+import dataclasses
 from typing import Any
 
 from graia.amnesia.message import MessageChain
-from graia.amnesia.message.element import Element, Unknown
+from graia.amnesia.message.element import Element, Text, Unknown
 from loguru import logger
 
 from ._serializer import _SERIALIZE_INV
-from .elements import _DESERIALIZE_INV
+from .elements import _DESERIALIZE_INV, FingerGuessing, ForwardCard, MarketFace
 
 
 def _deserialize_message(elements: list[dict[str, Any]]) -> MessageChain:
     elem_seq: list[Element] = []
     for e_data in elements:
-        cls = _DESERIALIZE_INV.get(e_data.pop("type"), None)
+        e_type = e_data.pop("type")
+        cls = _DESERIALIZE_INV.get(e_type, None) if e_type != "Unknown" else None
         if cls is None:
-            logger.warning(f"未知元素: {e_data!r}")
+            if e_type != "Unknown":
+                logger.warning(f"未知元素: {e_data!r}")
             elem_seq.append(Unknown("Unknown", e_data))
         else:
             elem_seq.append(cls(**e_data))
@@ -30,3 +33,27 @@ def _serialize_message(chain: MessageChain) -> list[dict[str, Any]]:
         else:
             raise TypeError(f"无法转换元素 {elem!r}")
     return res
+
+
+def _dump_element(elem: Element) -> dict[str, Any]:
+    if isinstance(elem, Unknown):
+        raw = elem.raw_data if isinstance(elem.raw_data, dict) else {"raw": elem.raw_data}
+        return {"type": "Unknown", **raw}
+    if isinstance(elem, MarketFace):
+        return {"type": "MarketFace", "raw": elem.raw}
+    if isinstance(elem, ForwardCard):
+        if elem.content.startswith("{"):
+            return {"type": "LightApp", "content": elem.content}
+        return {"type": "RichMessage", "service_id": 35, "content": elem.content}
+    if isinstance(elem, FingerGuessing):
+        return {"type": "FingerGuessing", "choice": elem.choice.name}
+    if isinstance(elem, Text):
+        return {"type": "Text", "text": elem.text}
+    data = {f.name: getattr(elem, f.name) for f in dataclasses.fields(elem) if not f.name.startswith("_")}
+    data["type"] = elem.__class__.__name__
+    return data
+
+
+def _dump_elements(chain: MessageChain) -> list[dict[str, Any]]:
+    """将消息链转换为包含全部字段的元素字典列表，与 `_deserialize_message` 互逆"""
+    return [_dump_element(elem) for elem in chain]
@@ -3,6 +3,21 @@ from __future__ import annotations
 
 class SealedMarketFace:  # Rust Native
     name: str
+    face_id: str
+    tab_id: int
+    key: str
+    item_type: int
+    sub_type: int
+    media_type: int
+    magic_value: str
+    width: int
+    height: int
+
+    @staticmethod
+    def build(
+        face_id: str, tab_id: int, key: str, name: str, *, media_type: int = 0, magic_value: str = ""
+    ) -> SealedMarketFace:
+        ...
 
 
 class SealedImage:  # Rust Native
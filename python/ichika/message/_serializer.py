@@ -20,6 +20,7 @@ from .elements import (
     MarketFace,
     Reply,
     RichMessage,
+    SuperFace,
 )
 
 _SERIALIZE_INV: dict[type, Callable[[Any], dict[str, Any]]] = {}
@@ -50,6 +51,7 @@ _serialize(At)(lambda t: {"target": t.target, "display": t.display})
 _serialize(Dice)(lambda t: {"value": t.value})
 _serialize(FingerGuessing)(lambda t: {"choice": t.choice.name})
 _serialize(Face)(lambda t: {"index": t.index})
+_serialize(SuperFace)(lambda t: {"index": t.index, "name": t.name, "large": t.large, "sticker_type": t.sticker_type})
 _serialize(MarketFace)(lambda t: {"raw": t.raw})
 _serialize(LightApp)(lambda t: {"content": t.content})
 _serialize(RichMessage)(lambda t: {"service_id": t.service_id, "content": t.content})
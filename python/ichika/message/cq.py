@@ -0,0 +1,30 @@
+"""CQ 码与消息链之间的转换"""
+from __future__ import annotations
+
+from graia.amnesia.message import MessageChain
+
+from .. import core
+from . import _deserialize_message, _dump_elements
+
+
+def chain_to_cq(chain: MessageChain) -> str:
+    """将消息链转换为 CQ 码字符串
+
+    :param chain: 消息链
+
+    :return: CQ 码字符串
+    """
+    return core.elements_to_cq(_dump_elements(chain))
+
+
+def cq_to_chain(text: str) -> MessageChain:
+    """将 CQ 码字符串转换为消息链
+
+    图片与音频会被转换为未上传的元素，发送时会自动下载并上传。
+    无法识别的 CQ 码会被转换为 `Unknown` 元素，可以无损地转换回 CQ 码。
+
+    :param text: CQ 码字符串
+
+    :return: 消息链
+    """
+    return _deserialize_message(core.cq_to_elements(text))
@@ -46,6 +46,7 @@ class At(Element):
     """@ 的目标的显示名，包括前导 @
 
     注意: 如果构造时不传入此参数，则会在手Q上显示为 “@{target}”，不过仍会起到通知效果。
+    发送群消息时可以传入 `resolve_at=True` 自动获取群名片或昵称。
 
     参见 [#59](https://github.com/BlueGlassBlock/Ichika/issues/59)
     """
@@ -105,10 +106,10 @@ class FingerGuessing(Element):
         """
         C = FingerGuessing.Choice
         if isinstance(choice, str):
-            self.choice = C[choice] if choice in C else C(choice)
-        if isinstance(choice, C):
-            self.choice = choice
-        raise TypeError(f"无效的猜拳参数：{choice}")
+            choice = C[choice] if choice in C.__members__ else C(choice)
+        if not isinstance(choice, C):
+            raise TypeError(f"无效的猜拳参数：{choice}")
+        self.choice = choice
 
     def __str__(self) -> str:
         return f"[猜拳: {self.choice.value}]"
@@ -147,12 +148,57 @@ class Face(Element):
         index = core.face_id_from_name(name)
         if index is None:
             raise ValueError("未知表情")
-        return cls(index, name)
+        return cls(index)
+
+    @property
+    def info(self) -> core.FaceInfo | None:
+        """表情在目录中的信息"""
+        return core.face_info(self.index)
 
     def __str__(self) -> str:
         return f"[表情: {self.name}]"
 
 
+@dataclass(init=False)
+class SuperFace(Element):
+    """超级表情元素
+
+    `large` 为 `False` 时为新版小表情，否则为大号动画表情。
+    """
+
+    index: int
+    """表情索引"""
+
+    name: str
+    """表情名称"""
+
+    large: bool
+    """是否为大号动画表情"""
+
+    sticker_type: int | None
+    """大号动画表情的贴纸类型"""
+
+    def __init__(
+        self, index: int, name: str | None = None, large: bool = True, sticker_type: int | None = None
+    ) -> None:
+        self.index = index
+        self.name = name or core.face_name_from_id(index)
+        self.large = large
+        self.sticker_type = sticker_type
+
+    @classmethod
+    def from_name(cls, name: str, large: bool = True) -> Self:
+        info = core.face_info_from_name(name)
+        if info is None:
+            raise ValueError("未知表情")
+        if large and not info.is_animated:
+            raise ValueError(f"表情 {info.name} 没有大号动画形式")
+        return cls(info.index, info.name, large, info.sticker_type if large else None)
+
+    def __str__(self) -> str:
+        return f"[超级表情: {self.name}]"
+
+
 @dataclass
 class MusicShare(Element):
     """音乐分享
@@ -264,7 +310,7 @@ class Audio(Generic[T_Audio], Element):
     """音频元素"""
 
     url: str
-    """音频链接"""
+    """音频链接，来自事件的音频为空字符串，需要通过 `Client.get_audio_url` 获取"""
     raw: T_Audio = field(compare=False)
     """原始音频数据"""
     _data_cache: bytes | None = field(repr=False, compare=False)
@@ -315,6 +361,8 @@ class Audio(Generic[T_Audio], Element):
         :return: 音频数据
         """
         if self._data_cache is None:
+            if not self.url:
+                raise ValueError("音频链接为空，请先通过 Client.get_audio_url 获取链接")
             if self.url.startswith("base64://"):
                 self._data_cache = base64.urlsafe_b64decode(self.url[8:])
             else:
@@ -458,11 +506,55 @@ class MarketFace(Element):
     def __init__(self, raw: SealedMarketFace) -> None:
         self.raw = raw
 
+    @classmethod
+    def build(cls, face_id: str, tab_id: int, key: str, name: str, media_type: int = 0) -> MarketFace:
+        """构造商城表情元素
+
+        :param face_id: 表情 ID，为十六进制字符串
+        :param tab_id: 表情包 ID
+        :param key: 表情密钥，可从收到的同一表情中获取
+        :param name: 表情名称，如 `[赞]`
+        :param media_type: 媒体类型
+
+        :return: 商城表情元素
+        """
+        return cls(core.SealedMarketFace.build(face_id, tab_id, key, name, media_type=media_type))
+
     @property
     def name(self) -> str:
         """表情名称"""
         return self.raw.name
 
+    @property
+    def face_id(self) -> str:
+        """表情 ID"""
+        return self.raw.face_id
+
+    @property
+    def tab_id(self) -> int:
+        """表情包 ID"""
+        return self.raw.tab_id
+
+    @property
+    def key(self) -> str:
+        """表情密钥"""
+        return self.raw.key
+
+    @property
+    def media_type(self) -> int:
+        """媒体类型"""
+        return self.raw.media_type
+
+    @property
+    def width(self) -> int:
+        """表情宽度"""
+        return self.raw.width
+
+    @property
+    def height(self) -> int:
+        """表情高度"""
+        return self.raw.height
+
     def __str__(self) -> str:
         return f"[商城表情:{self.name}]"
 
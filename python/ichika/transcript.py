@@ -0,0 +1,102 @@
+"""合并转发消息的聊天记录导出与导入"""
+from __future__ import annotations
+
+import pathlib
+from datetime import datetime
+from typing import Any, Literal, Tuple, Union
+
+from graia.amnesia.message import MessageChain
+
+from . import core
+from .client import Client
+from .core import CachedMessage, FriendMessage, GroupMessage
+from .message import _serialize_message as _serialize_msg
+from .message.elements import ForwardMessage, Image, Text
+
+TranscriptItem = Union[
+    ForwardMessage,
+    GroupMessage,
+    FriendMessage,
+    CachedMessage,
+    Tuple[int, datetime, MessageChain],
+]
+
+
+def _prepare_chain(chain: MessageChain, images: list[Image]) -> list[dict[str, Any]]:
+    content: list = []
+    for elem in chain:
+        if Image._check(elem):
+            if elem.raw is None:
+                elem = Text("[图片]")
+            else:
+                images.append(elem)
+        content.append(elem)
+    return _serialize_msg(MessageChain(content))
+
+
+def _prepare(item: TranscriptItem, images: list[Image]) -> dict[str, Any]:
+    if isinstance(item, ForwardMessage):
+        data: dict[str, Any] = {
+            "sender_id": item.sender_id,
+            "sender_name": item.sender_name,
+            "time": int(item.time.timestamp()),
+        }
+        if isinstance(item.content, MessageChain):
+            data["type"] = "Message"
+            data["content"] = _prepare_chain(item.content, images)
+        else:
+            data["type"] = "Forward"
+            data["content"] = [_prepare(f, images) for f in item.content]
+        return data
+    if isinstance(item, tuple):
+        sender, time, chain = item
+        name = str(sender)
+    else:
+        sender, time, chain = item.sender, item.source.time, item.content
+        info = getattr(item, "sender_info", None)
+        name = info.name if info is not None else str(sender)
+    return {
+        "type": "Message",
+        "sender_id": sender,
+        "sender_name": name,
+        "time": int(time.timestamp()),
+        "content": _prepare_chain(chain, images),
+    }
+
+
+async def export_transcript(
+    messages: list[TranscriptItem],
+    format: Literal["html", "markdown", "text", "json"] = "html",
+    *,
+    title: str = "聊天记录",
+    embed_images: bool = False,
+) -> str:
+    """将转发消息导出为聊天记录
+
+    :param messages: 转发消息（通常来自 [`download_forward_msg`][ichika.client.Client.download_forward_msg]）、消息事件、`CachedMessage` 或 `(发送者 QQ 号, 发送时间, 消息链)` 三元组的列表
+    :param format: 输出格式，`html` 为可独立打开的网页，`json` 格式可以被 [`load_transcript`][ichika.transcript.load_transcript] 重新读取
+    :param title: 聊天记录标题
+    :param embed_images: 是否下载图片并以 base64 形式内嵌，否则仅引用图片链接
+
+    :return: 渲染结果
+    """
+    images: list[Image] = []
+    data = [_prepare(msg, images) for msg in messages]
+    image_data: dict[str, bytes] = {}
+    if embed_images:
+        for img in images:
+            if img.url not in image_data:
+                image_data[img.url] = await img.fetch()
+    return core.render_transcript(data, format, title=title, images=image_data)
+
+
+def load_transcript(path: str | pathlib.Path) -> list[ForwardMessage]:
+    """读取 JSON 格式的聊天记录
+
+    读取出的图片尚未上传，可以直接用于 [`upload_forward_msg`][ichika.client.Client.upload_forward_msg]。
+
+    :param path: 文件路径
+
+    :return: 转发消息列表
+    """
+    return [Client._parse_downloaded_fwd(content) for content in core.load_transcript(path)]
@@ -0,0 +1,74 @@
+"""消息链与事件的 JSON 传输格式
+
+序列化结果带有版本号，图片、音频与商城表情会以 base64 编码的 protobuf 数据保存，
+因此可以在其他进程中反序列化后直接发送。
+"""
+from __future__ import annotations
+
+import json
+from typing import Any, Literal
+
+from graia.amnesia.message import MessageChain
+
+from . import core
+from .client import Client
+from .event_defs import Event
+from .message import _deserialize_message, _dump_elements
+
+
+def dump_chain(chain: MessageChain) -> str:
+    """将消息链序列化为 JSON
+
+    :param chain: 消息链
+
+    :return: JSON 字符串
+    """
+    return core.elements_to_json(_dump_elements(chain))
+
+
+def load_chain(data: str) -> MessageChain:
+    """从 JSON 反序列化消息链
+
+    :param data: JSON 字符串
+
+    :return: 消息链
+    """
+    return _deserialize_message(core.elements_from_json(data))
+
+
+def dump_event(event: Event | dict[str, Any]) -> str:
+    """将事件序列化为 JSON，`client` 字段会被忽略
+
+    :param event: 事件
+
+    :return: JSON 字符串
+    """
+    if isinstance(event, dict):
+        data = dict(event)
+    else:
+        data = {name: getattr(event, name) for name in event.__match_args__}
+        data["type_name"] = event.type_name
+    if isinstance(content := data.get("content"), MessageChain):
+        data["content"] = _dump_elements(content)
+    return core.event_to_json(data)
+
+
+def load_event(data: str, client: Client | None = None) -> Event:
+    """从 JSON 反序列化事件
+
+    :param data: JSON 字符串
+    :param client: 设置到事件 `client` 属性的客户端
+
+    :return: 事件
+    """
+    return core.event_from_json(data, client)
+
+
+def json_schema(kind: Literal["chain", "event"]) -> dict[str, Any]:
+    """获取传输格式的 JSON Schema
+
+    :param kind: `chain` 为消息链，`event` 为事件
+
+    :return: JSON Schema
+    """
+    return json.loads(core.wire_schema(kind))
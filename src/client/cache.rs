@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lru_time_cache::LruCache;
+use ricq::Client;
+
+use super::structs::{Friend, FriendList};
+
+/// 群成员显示名的短期缓存，用于补全 `At` 元素的显示文本。
+pub struct MemberNameCache {
+    inner: Mutex<LruCache<(i64, i64), String>>,
+}
+
+impl MemberNameCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(LruCache::with_expiry_duration_and_capacity(ttl, capacity)),
+        }
+    }
+
+    fn get(&self, group_uin: i64, uin: i64) -> Option<String> {
+        self.inner.lock().ok()?.get(&(group_uin, uin)).cloned()
+    }
+
+    fn insert(&self, group_uin: i64, uin: i64, name: String) {
+        if let Ok(mut cache) = self.inner.lock() {
+            cache.insert((group_uin, uin), name);
+        }
+    }
+
+    /// 获取群成员的 `@` 显示文本，优先使用群名片，其次为昵称。
+    ///
+    /// 获取失败的成员不会出现在返回值中。
+    pub async fn resolve(
+        &self,
+        client: &Client,
+        group_uin: i64,
+        uins: &[i64],
+    ) -> HashMap<i64, String> {
+        let mut res = HashMap::new();
+        for &uin in uins {
+            if res.contains_key(&uin) {
+                continue;
+            }
+            if let Some(name) = self.get(group_uin, uin) {
+                res.insert(uin, name);
+                continue;
+            }
+            match client.get_group_member_info(group_uin, uin).await {
+                Ok(info) => {
+                    let name = if info.card_name.is_empty() {
+                        info.nickname
+                    } else {
+                        info.card_name
+                    };
+                    let display = format!("@{name}");
+                    self.insert(group_uin, uin, display.clone());
+                    res.insert(uin, display);
+                }
+                Err(e) => {
+                    tracing::warn!("获取群 {} 成员 {} 的名片失败: {:?}", group_uin, uin, e);
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Default for MemberNameCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60), 4096)
+    }
+}
+
+/// 好友列表的短期缓存，用于获取单个好友。
+///
+/// 列表快照任务运行时由其在每次获取好友列表后更新，有效期持续到下次获取。
+#[derive(Default)]
+pub struct FriendListCache {
+    inner: Mutex<Option<(Instant, FriendList)>>,
+}
+
+impl FriendListCache {
+    /// 单独获取单个好友时缓存的有效期。
+    pub const TTL: Duration = Duration::from_secs(60);
+
+    pub fn store(&self, friends: FriendList, ttl: Duration) {
+        if let Ok(mut cache) = self.inner.lock() {
+            *cache = Some((Instant::now() + ttl, friends));
+        }
+    }
+
+    /// 在未过期的缓存中查找好友，缓存过期或不包含该好友时返回 `None`。
+    pub fn find(&self, uin: i64) -> Option<Friend> {
+        let cache = self.inner.lock().ok()?;
+        let (expires, friends) = cache.as_ref()?;
+        if *expires <= Instant::now() {
+            return None;
+        }
+        friends.find_friend(uin)
+    }
+}
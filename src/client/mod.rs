@@ -1,33 +1,55 @@
+mod cache;
 mod http;
-mod params;
+pub(crate) mod params;
+mod roster;
 pub mod structs;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
 use backon::{ExponentialBuilder, Retryable as _};
 use once_cell::sync::Lazy;
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{PyRuntimeError, PyTimeoutError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::*;
 use ricq::msg::elem::RQElem;
 use ricq::structs::{ForwardMessage, FriendAudio, GroupAudio, ProfileDetailUpdate, Status};
 use tokio::task::JoinHandle;
 
+use self::cache::{FriendListCache, MemberNameCache};
 use self::http::get_rust_client;
 use self::params::*;
+use self::roster::RosterIntervals;
 use self::structs::*;
+use crate::events::archive::MessageArchive;
+use crate::events::filter::EventFilter;
+use crate::events::history::CachedMessage;
+use crate::events::model::Event;
+use crate::events::{EventDispatcher, Subscriber, Subscription};
 use crate::exc::IckResult;
 use crate::login::{reconnect, TokenRW};
 use crate::message::convert::{
     deserialize_message_chain,
+    deserialize_message_chain_with,
     render_forward,
     serialize_audio_dict,
     serialize_element,
     serialize_forward,
+    unresolved_at_targets,
+};
+use crate::message::elements::{market_face_pack_url, MarketFacePack, SealedAudio};
+use crate::message::wire::{WireChain, WireElement};
+use crate::utils::{
+    permission_name,
+    py_client_refs,
+    py_future,
+    py_none,
+    py_try,
+    py_use,
+    to_py_gender,
+    AsPython,
 };
-use crate::message::elements::SealedAudio;
-use crate::utils::{py_future, py_none, py_try, py_use, to_py_gender, AsPython};
 static RETRY_BUILDER: Lazy<ExponentialBuilder> = Lazy::new(|| {
     ExponentialBuilder::default()
         .with_factor(1.5)
@@ -43,6 +65,9 @@ pub struct PlumbingClient {
     #[pyo3(get)]
     uin: i64,
     token_rw: TokenRW,
+    member_names: Arc<MemberNameCache>,
+    friends: Arc<FriendListCache>,
+    dispatcher: Arc<EventDispatcher>,
 }
 
 /// 用于向 Python 内的 `ichika.client.Client` 传递初始值
@@ -53,6 +78,7 @@ pub struct ClientInitializer {
     pub client: Arc<ricq::Client>,
     pub alive: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>,
     pub token_rw: TokenRW,
+    pub dispatcher: Arc<EventDispatcher>,
 }
 
 #[pymethods]
@@ -68,6 +94,9 @@ impl PlumbingClient {
                 .take(),
             uin: init.uin,
             token_rw: init.token_rw,
+            member_names: Arc::new(MemberNameCache::default()),
+            friends: Arc::new(FriendListCache::default()),
+            dispatcher: init.dispatcher,
         })
     }
 
@@ -76,26 +105,75 @@ impl PlumbingClient {
         let token_rw = self.token_rw.clone();
         let alive = self.alive.take();
         let uin = self.uin;
+        let dispatcher = self.dispatcher.clone();
         py_future(py, async move {
             if let Some(mut alive) = alive {
                 loop {
-                    alive
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
-
                     // 断线重连
-                    if let Some(handle) = reconnect(&client, &token_rw).await? {
-                        alive = handle;
-                    } else {
-                        break;
+                    let res = match alive.await {
+                        Ok(()) => reconnect(&client, &token_rw, &dispatcher).await,
+                        Err(e) => Err(PyRuntimeError::new_err(format!(
+                            "客户端连接任务异常退出: {e}"
+                        ))),
+                    };
+                    match res {
+                        Ok(Some(handle)) => {
+                            alive = handle;
+                            dispatcher.dispatch(Event::Reconnected).await;
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::error!("客户端 {} 重连失败，不再重连: {}", uin, e);
+                            dispatcher
+                                .dispatch(Event::LoggedOut {
+                                    error: Some(e.to_string()),
+                                })
+                                .await;
+                            return Err(e.into());
+                        }
                     }
                 }
             }
             tracing::info!("客户端 {} 被迫断开连接", uin);
+            dispatcher.dispatch(Event::LoggedOut { error: None }).await;
             Ok(py_none())
         })
     }
 
+    #[pyo3(signature = (interval = 300.0, group_intervals = None))]
+    pub fn watch_roster<'py>(
+        &self,
+        py: Python<'py>,
+        interval: f64,
+        group_intervals: Option<HashMap<i64, f64>>,
+    ) -> PyResult<&'py PyAny> {
+        let to_duration = |secs: f64| {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|e| PyValueError::new_err(format!("无效的获取间隔: {e}")))
+        };
+        if interval <= 0.0 {
+            return Err(PyValueError::new_err("获取间隔必须大于 0"));
+        }
+        let intervals = RosterIntervals {
+            default: to_duration(interval)?,
+            groups: group_intervals
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(group_uin, secs)| {
+                    let interval = (secs > 0.0).then(|| to_duration(secs)).transpose()?;
+                    Ok((group_uin, interval))
+                })
+                .collect::<PyResult<_>>()?,
+        };
+        let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
+        let friends = self.friends.clone();
+        py_future(py, async move {
+            self::roster::watch(client, dispatcher, friends, intervals).await;
+            Ok(())
+        })
+    }
+
     #[getter]
     pub fn online(&self) -> bool {
         self.client
@@ -103,6 +181,110 @@ impl PlumbingClient {
             .load(std::sync::atomic::Ordering::Acquire)
     }
 
+    #[pyo3(signature = (consumer, filter = None))]
+    pub fn subscribe(&self, mut consumer: Subscriber, filter: Option<EventFilter>) -> Subscription {
+        if let Some(filter) = filter {
+            consumer.set_filter(filter);
+        }
+        Subscription {
+            uin: self.uin,
+            id: self.dispatcher.subscribe(consumer),
+        }
+    }
+
+    /// 当前有效的订阅，按添加顺序排列，登录时传入的事件队列在最前。
+    #[getter]
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.dispatcher
+            .subscription_ids()
+            .into_iter()
+            .map(|id| Subscription { uin: self.uin, id })
+            .collect()
+    }
+
+    /// 取消订阅，返回订阅是否仍然有效。
+    pub fn unsubscribe(&self, subscription: Subscription) -> bool {
+        subscription.uin == self.uin && self.dispatcher.unsubscribe(subscription.id)
+    }
+
+    #[pyo3(signature = (filter, timeout = None, consume = false))]
+    pub fn wait_for<'py>(
+        &self,
+        py: Python<'py>,
+        filter: EventFilter,
+        timeout: Option<f64>,
+        consume: bool,
+    ) -> PyResult<&'py PyAny> {
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(format!("无效的超时时间: {e}")))?;
+        let (id, rx) = self.dispatcher.waiters().register(filter, consume);
+        let dispatcher = self.dispatcher.clone();
+        let uin = self.uin;
+        py_future(py, async move {
+            let event = dispatcher
+                .waiters()
+                .wait(id, rx, timeout)
+                .await
+                .ok_or_else(|| PyTimeoutError::new_err("等待事件超时"))?;
+            let event = py_try(|py| {
+                let client = py_client_refs(py).get_item(uin)?;
+                event.into_py_event(py, client.into_py(py))
+            })?;
+            Ok(event)
+        })
+    }
+
+    /// 从最近消息的缓存中获取消息，`source` 为 `MessageSource` 或 `RawMessageReceipt`。
+    ///
+    /// `RawMessageReceipt` 自带会话信息，`MessageSource` 需要指定 `kind` 与 `target`。
+    #[pyo3(signature = (source, kind = None, target = None))]
+    pub fn get_message(
+        &self,
+        py: Python,
+        source: &PyAny,
+        kind: Option<String>,
+        target: Option<i64>,
+    ) -> PyResult<Option<CachedMessage>> {
+        let seq: i32 = source.getattr("seq")?.extract()?;
+        let rand: i32 = source.getattr("rand")?.extract()?;
+        let receipt = source.extract::<RawMessageReceipt>().ok();
+        let (Some(kind), Some(target)) = (
+            kind.or_else(|| receipt.as_ref().map(|r| r.kind.clone())),
+            target.or_else(|| receipt.as_ref().map(|r| r.target)),
+        ) else {
+            return Err(PyValueError::new_err(
+                "通过 MessageSource 获取消息时需要指定 kind 与 target",
+            ));
+        };
+        let group = match kind.as_str() {
+            "group" => true,
+            "friend" => false,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "消息类型必须为 group 或 friend: {other}"
+                )))
+            }
+        };
+        self.dispatcher
+            .history()
+            .get(group, target, seq, rand)
+            .map(|entry| entry.into_py_message(py))
+            .transpose()
+    }
+
+    /// 登录时指定的消息存档，未指定时为 `None`。
+    #[getter]
+    pub fn archive(&self) -> Option<MessageArchive> {
+        self.dispatcher.archive().cloned()
+    }
+
+    /// 各事件类型的投递失败次数。
+    pub fn delivery_errors(&self) -> HashMap<String, u64> {
+        self.dispatcher.dead_letters().errors()
+    }
+
     pub fn stop<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
         py_future(py, async move {
@@ -241,6 +423,25 @@ impl PlumbingClient {
         })
     }
 
+    /// 获取单个好友，不是好友时返回 `None`。
+    ///
+    /// 协议没有查询单个好友的接口，优先从好友列表的短期缓存中查找，未找到时获取完整的好友列表。
+    pub fn get_friend<'py>(&self, py: Python<'py>, uin: i64) -> PyResult<&'py PyAny> {
+        let client = self.client.clone();
+        let cache = self.friends.clone();
+        py_future(py, async move {
+            if let Some(friend) = cache.find(uin) {
+                return Ok(Some(friend));
+            }
+            let fetch_closure =
+                async || -> IckResult<FriendList> { Ok(client.get_friend_list().await?.into()) };
+            let friends = fetch_closure.retry(&*RETRY_BUILDER).await?;
+            let friend = friends.find_friend(uin);
+            cache.store(friends, FriendListCache::TTL);
+            Ok(friend)
+        })
+    }
+
     pub fn nudge_friend<'py>(&self, py: Python<'py>, uin: i64) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
         py_future(py, async move {
@@ -277,6 +478,15 @@ impl PlumbingClient {
         })
     }
 
+    /// 获取单个群的最新信息，包含最后一条消息的序列号，不在群中时返回 `None`。
+    pub fn get_group<'py>(&self, py: Python<'py>, uin: i64) -> PyResult<&'py PyAny> {
+        let client = self.client.clone();
+        py_future(py, async move {
+            let info = client.get_group_info(uin).await?;
+            Ok(info.map(|g| Group::from(g).obj()))
+        })
+    }
+
     pub fn mute_group<'py>(&self, py: Python<'py>, uin: i64, mute: bool) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
         py_future(py, async move {
@@ -331,15 +541,45 @@ impl PlumbingClient {
         group_owner_uin: i64,
     ) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
         py_future(py, async move {
             let members = client
                 .get_group_member_list(group_uin, group_owner_uin)
                 .await?;
+            for info in &members {
+                dispatcher
+                    .roles()
+                    .insert(group_uin, info.uin, permission_name(&info.permission));
+            }
             let members = members.into_iter().map(Member::from).collect::<Vec<_>>();
             Ok(members)
         })
     }
 
+    /// 获取单个群成员，无需获取整个成员列表，不是群成员时抛出 `ValueError`。
+    pub fn get_member<'py>(
+        &self,
+        py: Python<'py>,
+        group_uin: i64,
+        uin: i64,
+    ) -> PyResult<&'py PyAny> {
+        let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
+        py_future(py, async move {
+            let info = client.get_group_member_info(group_uin, uin).await?;
+            // 不是群成员时服务器返回空的成员信息
+            if info.uin != uin {
+                return Err(
+                    PyValueError::new_err(format!("{uin} 不是群 {group_uin} 的成员")).into(),
+                );
+            }
+            dispatcher
+                .roles()
+                .insert(group_uin, info.uin, permission_name(&info.permission));
+            Ok(Member::from(info))
+        })
+    }
+
     pub fn nudge_member<'py>(
         &self,
         py: Python<'py>,
@@ -511,9 +751,12 @@ impl PlumbingClient {
         audio: PyObject,
     ) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
         let ptt = audio.extract::<SealedAudio>(py)?.inner;
         py_future(py, async move {
+            let content = WireChain(vec![WireElement::audio(String::new(), &ptt)]);
             let receipt = client.send_friend_audio(uin, FriendAudio(ptt)).await?;
+            dispatcher.record_sent(false, uin, &receipt, content);
             Ok(RawMessageReceipt::new(receipt, "friend", uin)?)
         })
     }
@@ -525,13 +768,47 @@ impl PlumbingClient {
         audio: PyObject,
     ) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
         let ptt = audio.extract::<SealedAudio>(py)?.inner;
         py_future(py, async move {
+            let content = WireChain(vec![WireElement::audio(String::new(), &ptt)]);
             let receipt = client.send_group_audio(uin, GroupAudio(ptt)).await?;
+            dispatcher.record_sent(true, uin, &receipt, content);
             Ok(RawMessageReceipt::new(receipt, "group", uin)?)
         })
     }
 
+    /// 获取语音的下载链接，`target` 为群号或发送语音的好友 QQ 号。
+    pub fn get_audio_url<'py>(
+        &self,
+        py: Python<'py>,
+        audio: PyObject,
+        kind: &str,
+        target: i64,
+    ) -> PyResult<&'py PyAny> {
+        let client = self.client.clone();
+        let ptt = audio.extract::<SealedAudio>(py)?.inner;
+        let is_group = match kind {
+            "group" => true,
+            "friend" => false,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "语音来源类型必须为 group 或 friend: {other}"
+                )))
+            }
+        };
+        py_future(py, async move {
+            let url = if is_group {
+                client.get_group_audio_url(target, GroupAudio(ptt)).await?
+            } else {
+                client
+                    .get_friend_audio_url(target, FriendAudio(ptt))
+                    .await?
+            };
+            Ok(url)
+        })
+    }
+
     pub fn send_friend_music_share<'py>(
         &self,
         py: Python<'py>,
@@ -605,6 +882,28 @@ impl PlumbingClient {
             })?)
         })
     }
+
+    pub fn fetch_market_face_pack<'py>(
+        &self,
+        py: Python<'py>,
+        downloader: &'py PyAny,
+        tab_id: i32,
+    ) -> PyResult<&'py PyAny> {
+        use ricq::ext::http::{HttpClient as _, HttpMethod};
+
+        let mut http_client = get_rust_client(py, downloader)?;
+        py_future(py, async move {
+            let data = http_client
+                .make_request(
+                    HttpMethod::GET,
+                    market_face_pack_url(tab_id),
+                    &Default::default(),
+                    bytes::Bytes::new(),
+                )
+                .await?;
+            Ok(py_try(|py| MarketFacePack::parse(py, tab_id, &data))?)
+        })
+    }
 }
 
 #[pymethods]
@@ -616,23 +915,48 @@ impl PlumbingClient {
         chain: &'py PyList,
     ) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
+        let dispatcher = self.dispatcher.clone();
         let chain = deserialize_message_chain(chain)?;
         py_future(py, async move {
+            let content = WireChain::from(chain.clone());
             let receipt = client.send_friend_message(uin, chain).await?;
+            dispatcher.record_sent(false, uin, &receipt, content);
             Ok(RawMessageReceipt::new(receipt, "friend", uin)?)
         })
     }
 
+    #[pyo3(signature = (uin, chain, *, resolve_at=false))]
     pub fn send_group_message<'py>(
         &self,
         py: Python<'py>,
         uin: i64,
         chain: &'py PyList,
+        resolve_at: bool,
     ) -> PyResult<&'py PyAny> {
         let client = self.client.clone();
-        let chain = deserialize_message_chain(chain)?;
+        let dispatcher = self.dispatcher.clone();
+        let targets = if resolve_at {
+            unresolved_at_targets(chain)?
+        } else {
+            vec![]
+        };
+        if targets.is_empty() {
+            let chain = deserialize_message_chain(chain)?;
+            return py_future(py, async move {
+                let content = WireChain::from(chain.clone());
+                let receipt = client.send_group_message(uin, chain).await?;
+                dispatcher.record_sent(true, uin, &receipt, content);
+                Ok(RawMessageReceipt::new(receipt, "group", uin)?)
+            });
+        }
+        let member_names = self.member_names.clone();
+        let chain: Py<PyList> = chain.into_py(py);
         py_future(py, async move {
+            let displays = member_names.resolve(&client, uin, &targets).await;
+            let chain = py_try(|py| deserialize_message_chain_with(chain.as_ref(py), &displays))?;
+            let content = WireChain::from(chain.clone());
             let receipt = client.send_group_message(uin, chain).await?;
+            dispatcher.record_sent(true, uin, &receipt, content);
             Ok(RawMessageReceipt::new(receipt, "group", uin)?)
         })
     }
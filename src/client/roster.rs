@@ -0,0 +1,123 @@
+//! 定期获取好友、群与群成员列表，并将与上次快照的差异作为事件投递。
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ricq::Client;
+use tokio::time::Instant;
+
+use super::cache::FriendListCache;
+use crate::events::EventDispatcher;
+use crate::utils::permission_name;
+
+/// 客户端离线时再次检查的间隔。
+const OFFLINE_RETRY: Duration = Duration::from_secs(5);
+
+/// 快照的获取间隔。
+pub struct RosterIntervals {
+    /// 好友列表与群列表的获取间隔，也是群成员列表的默认获取间隔
+    pub default: Duration,
+    /// 单独设置的群成员列表获取间隔，为 `None` 时不获取该群的成员列表
+    pub groups: HashMap<i64, Option<Duration>>,
+}
+
+impl RosterIntervals {
+    fn of_group(&self, group_uin: i64) -> Option<Duration> {
+        self.groups
+            .get(&group_uin)
+            .copied()
+            .unwrap_or(Some(self.default))
+    }
+}
+
+/// 持续比较列表快照，直到任务被取消。
+pub async fn watch(
+    client: Arc<Client>,
+    dispatcher: Arc<EventDispatcher>,
+    friends: Arc<FriendListCache>,
+    intervals: RosterIntervals,
+) {
+    let roster = dispatcher.roster();
+    let mut lists_due = Instant::now();
+    // 群号到 (群主 QQ 号, 下次获取成员列表的时间)
+    let mut groups: HashMap<i64, (i64, Instant)> = HashMap::new();
+    loop {
+        let next = groups
+            .values()
+            .map(|(_, due)| *due)
+            .fold(lists_due, Instant::min);
+        tokio::time::sleep_until(next).await;
+        if !client.online.load(Ordering::Acquire) {
+            tokio::time::sleep(OFFLINE_RETRY).await;
+            continue;
+        }
+
+        let now = Instant::now();
+        let mut events = vec![];
+        if lists_due <= now {
+            lists_due = now + intervals.default;
+            match client.get_friend_list().await {
+                Ok(resp) => {
+                    events.extend(
+                        roster.diff_friends(resp.friends.iter().map(|f| (f.uin, f.remark.clone()))),
+                    );
+                    friends.store(resp.into(), intervals.default);
+                }
+                Err(e) => tracing::warn!("获取好友列表快照失败: {:?}", e),
+            }
+            match client.get_group_list().await {
+                Ok(infos) => {
+                    let mut known = HashMap::new();
+                    for info in &infos {
+                        let due = groups.get(&info.code).map_or(now, |(_, due)| *due);
+                        known.insert(info.code, (info.owner_uin, due));
+                    }
+                    groups = known;
+                    events.extend(
+                        roster.diff_groups(infos.into_iter().map(|info| (info.code, info.memo))),
+                    );
+                }
+                Err(e) => tracing::warn!("获取群列表快照失败: {:?}", e),
+            }
+        }
+
+        for (&group_uin, (owner_uin, due)) in groups.iter_mut() {
+            if *due > now {
+                continue;
+            }
+            let Some(interval) = intervals.of_group(group_uin) else {
+                *due = now + intervals.default;
+                continue;
+            };
+            *due = now + interval;
+            let fetched_at = Instant::now();
+            match client.get_group_member_list(group_uin, *owner_uin).await {
+                Ok(members) => {
+                    for info in &members {
+                        dispatcher.roles().insert(
+                            group_uin,
+                            info.uin,
+                            permission_name(&info.permission),
+                        );
+                    }
+                    events.extend(
+                        roster.diff_members(
+                            group_uin,
+                            members
+                                .into_iter()
+                                .map(|info| (info.uin, info.card_name, info.special_title)),
+                            fetched_at,
+                        ),
+                    );
+                }
+                Err(e) => tracing::warn!("获取群 {} 成员列表快照失败: {:?}", group_uin, e),
+            }
+        }
+
+        for event in events {
+            dispatcher.dispatch(event).await;
+        }
+    }
+}
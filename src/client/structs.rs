@@ -8,6 +8,8 @@ use ricq::structs::{FriendGroupInfo, FriendInfo, GroupInfo, GroupMemberInfo, Mes
 use ricq_core::command::friendlist::FriendListResponse;
 use ricq_core::command::oidb_svc::OcrResponse;
 use ricq_core::structs::SummaryCardInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::utils::{datetime_from_ts, py_try, py_use, to_py_gender, to_py_permission};
 #[pyclass(get_all, module = "ichika.core")]
@@ -280,7 +282,7 @@ impl Member {
 }
 
 #[pyclass(get_all, module = "ichika.core")]
-#[derive(PyRepr, Clone)]
+#[derive(PyRepr, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Friend {
     pub uin: i64,
     pub nick: String,
@@ -0,0 +1,303 @@
+//! 消息的本地存档。
+//!
+//! 存档为 SQLite 数据库，记录接收到的与当前客户端发送的群消息和好友消息。
+//! 消息内容以带版本号的 JSON 消息链（[`ChainEnvelope`]）保存，同时保存纯文本用于全文检索。
+//! 多个账号可以共用同一个存档文件，存档中的消息按账号区分。
+//! 写入在单独的线程中按顺序进行，不会阻塞事件处理与消息发送。
+
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+
+use super::history::{CachedMessage, HistoryEntry};
+use super::model::{timestamp, Source};
+use crate::message::wire::{check_version, ChainEnvelope, WIRE_VERSION};
+use crate::utils::{py_future, py_try};
+
+const SCHEMA: &str = "
+PRAGMA journal_mode = WAL;
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY,
+    uin INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    sender INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    seq INTEGER NOT NULL,
+    rand INTEGER NOT NULL,
+    seqs TEXT NOT NULL,
+    rands TEXT NOT NULL,
+    content TEXT NOT NULL,
+    text TEXT NOT NULL,
+    UNIQUE (uin, kind, target, seq, rand)
+);
+CREATE INDEX IF NOT EXISTS messages_conversation ON messages (uin, kind, target, time);
+CREATE INDEX IF NOT EXISTS messages_sender ON messages (uin, sender, time);
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_text
+    USING fts5 (text, content = 'messages', content_rowid = 'id', tokenize = 'trigram');
+CREATE TRIGGER IF NOT EXISTS messages_text_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_text (rowid, text) VALUES (new.id, new.text);
+END;
+";
+
+/// 三元组分词器能够匹配的最短文本长度，更短的文本使用 `LIKE` 匹配。
+const TRIGRAM_MIN_CHARS: usize = 3;
+
+/// 存档的查询条件，为 `None` 的条件不参与筛选。
+struct ArchiveQuery {
+    uin: Option<i64>,
+    kind: Option<String>,
+    target: Option<i64>,
+    sender: Option<i64>,
+    start: Option<i64>,
+    end: Option<i64>,
+    text: Option<String>,
+    limit: u32,
+    newest_first: bool,
+}
+
+impl ArchiveQuery {
+    fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        let mut push = |condition: &str, value: Value| {
+            conditions.push(condition.to_owned());
+            values.push(value);
+        };
+        if let Some(uin) = self.uin {
+            push("uin = ?", uin.into());
+        }
+        if let Some(kind) = &self.kind {
+            push("kind = ?", kind.clone().into());
+        }
+        if let Some(target) = self.target {
+            push("target = ?", target.into());
+        }
+        if let Some(sender) = self.sender {
+            push("sender = ?", sender.into());
+        }
+        if let Some(start) = self.start {
+            push("time >= ?", start.into());
+        }
+        if let Some(end) = self.end {
+            push("time < ?", end.into());
+        }
+        if let Some(text) = &self.text {
+            if text.chars().count() >= TRIGRAM_MIN_CHARS {
+                push(
+                    "id IN (SELECT rowid FROM messages_text WHERE messages_text MATCH ?)",
+                    format!("\"{}\"", text.replace('"', "\"\"")).into(),
+                );
+            } else {
+                let escaped = text
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                push("text LIKE ? ESCAPE '\\'", format!("%{escaped}%").into());
+            }
+        }
+        let mut sql =
+            "SELECT kind, target, sender, time, seqs, rands, content FROM messages".to_owned();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        let order = if self.newest_first { "DESC" } else { "ASC" };
+        sql.push_str(&format!(" ORDER BY time {order}, id {order} LIMIT ?"));
+        values.push(self.limit.into());
+        (sql, values)
+    }
+}
+
+/// 消息的本地存档。
+#[pyclass(module = "ichika.core")]
+#[derive(Clone)]
+pub struct MessageArchive {
+    conn: Arc<Mutex<Connection>>,
+    /// 写入线程的发送端，所有发送端被丢弃后写入线程退出
+    writer: Sender<(i64, HistoryEntry)>,
+}
+
+impl MessageArchive {
+    pub fn open(path: PathBuf) -> PyResult<Self> {
+        let res: rusqlite::Result<Connection> = try {
+            let conn = Connection::open(&path)?;
+            conn.busy_timeout(std::time::Duration::from_secs(5))?;
+            conn.execute_batch(SCHEMA)?;
+            conn
+        };
+        let conn = res
+            .map_err(|e| PyIOError::new_err(format!("无法打开消息存档 {}: {e}", path.display())))?;
+        let conn = Arc::new(Mutex::new(conn));
+        let (writer, rx) = channel::<(i64, HistoryEntry)>();
+        std::thread::Builder::new()
+            .name("ichika-archive".into())
+            .spawn({
+                let conn = conn.clone();
+                move || {
+                    for (uin, entry) in rx {
+                        Self::write(&conn, uin, &entry);
+                    }
+                }
+            })
+            .map_err(|e| PyIOError::new_err(format!("无法启动消息存档写入线程: {e}")))?;
+        Ok(Self { conn, writer })
+    }
+
+    /// 存档消息，已存档的消息会被忽略。
+    ///
+    /// 消息交给写入线程后立即返回。
+    pub fn insert(&self, uin: i64, entry: HistoryEntry) {
+        if self.writer.send((uin, entry)).is_err() {
+            tracing::error!("写入消息存档失败: 写入线程已退出");
+        }
+    }
+
+    fn write(conn: &Mutex<Connection>, uin: i64, entry: &HistoryEntry) {
+        let (Some(&seq), Some(&rand)) = (entry.source.seqs.first(), entry.source.rands.first())
+        else {
+            return;
+        };
+        let res: Result<(), Box<dyn std::error::Error>> = try {
+            let content = serde_json::to_string(&ChainEnvelope {
+                version: WIRE_VERSION,
+                elements: entry.content.clone(),
+            })?;
+            let conn = conn.lock().map_err(|e| e.to_string())?;
+            conn.prepare_cached(
+                "INSERT OR IGNORE INTO messages (uin, kind, target, sender, time, seq, rand, \
+                 seqs, rands, content, text) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                uin,
+                entry.kind(),
+                entry.target,
+                entry.sender,
+                entry.source.time,
+                seq,
+                rand,
+                serde_json::to_string(&entry.source.seqs)?,
+                serde_json::to_string(&entry.source.rands)?,
+                content,
+                entry.content.plain_text(),
+            ])?;
+        };
+        if let Err(e) = res {
+            tracing::error!("写入消息存档失败: {}", e);
+        }
+    }
+
+    fn query(&self, query: &ArchiveQuery) -> PyResult<Vec<HistoryEntry>> {
+        let (sql, values) = query.to_sql();
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| PyRuntimeError::new_err(format!("消息存档不可用: {e}")))?;
+        let res: rusqlite::Result<Vec<_>> = try {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i32>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let rows = res.map_err(|e| PyIOError::new_err(format!("查询消息存档失败: {e}")))?;
+        rows.into_iter()
+            .map(|(kind, target, sender, time, seqs, rands, content)| {
+                let parse_err = |e: serde_json::Error| {
+                    PyValueError::new_err(format!("无法解析存档的消息: {e}"))
+                };
+                let envelope: ChainEnvelope = serde_json::from_str(&content).map_err(parse_err)?;
+                check_version(envelope.version)?;
+                Ok(HistoryEntry {
+                    group: kind == "group",
+                    target,
+                    sender,
+                    source: Source {
+                        seqs: serde_json::from_str(&seqs).map_err(parse_err)?,
+                        rands: serde_json::from_str(&rands).map_err(parse_err)?,
+                        time,
+                    },
+                    content: envelope.elements,
+                })
+            })
+            .collect()
+    }
+}
+
+#[pymethods]
+impl MessageArchive {
+    /// 打开存档文件，用于在不登录的情况下查询存档。
+    #[staticmethod]
+    #[pyo3(name = "open")]
+    pub fn py_open(path: PathBuf) -> PyResult<Self> {
+        Self::open(path)
+    }
+
+    #[pyo3(signature = (
+        *,
+        uin = None,
+        kind = None,
+        target = None,
+        sender = None,
+        start = None,
+        end = None,
+        text = None,
+        limit = 100,
+        newest_first = false
+    ))]
+    #[allow(clippy::too_many_arguments, reason = "Required for Python binding")]
+    pub fn query<'py>(
+        &self,
+        py: Python<'py>,
+        uin: Option<i64>,
+        kind: Option<String>,
+        target: Option<i64>,
+        sender: Option<i64>,
+        start: Option<&PyAny>,
+        end: Option<&PyAny>,
+        text: Option<String>,
+        limit: u32,
+        newest_first: bool,
+    ) -> PyResult<&'py PyAny> {
+        if let Some(kind) = &kind && kind != "group" && kind != "friend" {
+            return Err(PyValueError::new_err(format!("无效的消息类型: {kind}")));
+        }
+        let query = ArchiveQuery {
+            uin,
+            kind,
+            target,
+            sender,
+            start: start.map(timestamp).transpose()?,
+            end: end.map(timestamp).transpose()?,
+            text: text.filter(|text| !text.is_empty()),
+            limit,
+            newest_first,
+        };
+        let archive = self.clone();
+        py_future(py, async move {
+            let entries = tokio::task::spawn_blocking(move || archive.query(&query))
+                .await
+                .map_err(|e| PyRuntimeError::new_err(format!("查询消息存档失败: {e}")))??;
+            let messages = py_try(|py| {
+                entries
+                    .into_iter()
+                    .map(|entry| entry.into_py_message(py))
+                    .collect::<PyResult<Vec<CachedMessage>>>()
+            })?;
+            Ok(messages)
+        })
+    }
+}
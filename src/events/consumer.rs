@@ -0,0 +1,150 @@
+//! 事件的消费者。
+//!
+//! `put` 为协程函数的消费者（包括 `asyncio.Queue`）的协程默认在登录时的事件循环上运行，
+//! 也可以指定其他线程的事件循环；
+//! 同步的 `put` 方法（如 `queue.Queue`）与不带 `put` 方法的可调用对象会在阻塞线程池中被同步调用，
+//! 需要自行保证线程安全。
+
+use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+use pyo3::intern;
+use pyo3::prelude::*;
+use pyo3_asyncio::{into_future_with_locals, TaskLocals};
+
+use crate::utils::{py_try, py_use};
+
+pub enum Consumer {
+    /// 带有 `put` 协程方法的对象，`locals` 为空时使用登录时的事件循环
+    Queue {
+        queue: PyObject,
+        locals: Option<TaskLocals>,
+    },
+    /// 线程安全的同步可调用对象
+    Callable(PyObject),
+}
+
+impl Consumer {
+    pub fn clone_ref(&self, py: Python) -> Self {
+        match self {
+            Consumer::Queue { queue, locals } => Consumer::Queue {
+                queue: queue.clone_ref(py),
+                locals: locals.clone(),
+            },
+            Consumer::Callable(callable) => Consumer::Callable(callable.clone_ref(py)),
+        }
+    }
+
+    /// 指定运行 `put` 协程的事件循环。
+    pub fn set_event_loop(&mut self, event_loop: &PyAny) -> PyResult<()> {
+        let Consumer::Queue { locals, .. } = self else {
+            return Err(PyTypeError::new_err(
+                "同步可调用对象在阻塞线程池中调用，不能指定事件循环",
+            ));
+        };
+        *locals = Some(TaskLocals::new(event_loop).copy_context(event_loop.py())?);
+        Ok(())
+    }
+
+    /// 将事件交给消费者并等待处理完成。
+    pub async fn put(&self, default_locals: &TaskLocals, event: PyObject) -> PyResult<()> {
+        match self {
+            Consumer::Queue { queue, locals } => {
+                py_try(|py| {
+                    into_future_with_locals(
+                        locals.as_ref().unwrap_or(default_locals),
+                        queue
+                            .as_ref(py)
+                            .getattr(intern!(py, "put"))?
+                            .call1((event,))?,
+                    )
+                })?
+                .await?;
+            }
+            Consumer::Callable(callable) => {
+                let callable = py_use(|py| callable.clone_ref(py));
+                tokio::task::spawn_blocking(move || py_try(|py| callable.call1(py, (event,))))
+                    .await
+                    .map_err(|e| PyRuntimeError::new_err(format!("同步回调执行失败: {e}")))??;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'py> FromPyObject<'py> for Consumer {
+    fn extract(ob: &'py PyAny) -> PyResult<Self> {
+        let py = ob.py();
+        if ob.hasattr(intern!(py, "put"))? {
+            let put = ob.getattr(intern!(py, "put"))?;
+            let async_queue = py
+                .import(intern!(py, "asyncio"))?
+                .getattr(intern!(py, "Queue"))?;
+            let is_coroutine = py
+                .import(intern!(py, "inspect"))?
+                .getattr(intern!(py, "iscoroutinefunction"))?
+                .call1((put,))?
+                .is_true()?;
+            if ob.is_instance(async_queue)? || is_coroutine {
+                Ok(Consumer::Queue {
+                    queue: ob.into_py(py),
+                    locals: None,
+                })
+            } else {
+                Ok(Consumer::Callable(put.into_py(py)))
+            }
+        } else if ob.is_callable() {
+            Ok(Consumer::Callable(ob.into_py(py)))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "事件消费者必须带有 put 方法或为可调用对象: {ob}"
+            )))
+        }
+    }
+}
+
+/// 检查对象是否为 [`asyncio.AbstractEventLoop`] 的实例。
+pub fn is_event_loop(ob: &PyAny) -> PyResult<bool> {
+    let py = ob.py();
+    let base = py
+        .import(intern!(py, "asyncio"))?
+        .getattr(intern!(py, "AbstractEventLoop"))?;
+    ob.is_instance(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    #[test]
+    fn sync_queue_is_called_in_thread_pool() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let locals = PyDict::new(py);
+            py.run(
+                "import queue\n\
+                 class AsyncQueue:\n    async def put(self, event): pass\n\
+                 sync_queue = queue.Queue()\n\
+                 async_queue = AsyncQueue()",
+                None,
+                Some(locals),
+            )?;
+            let sync_queue = locals.get_item("sync_queue").unwrap();
+            let Consumer::Callable(put) = sync_queue.extract()? else {
+                panic!("queue.Queue 不应被视为异步队列");
+            };
+            put.call1(py, ("event",))?;
+            assert_eq!(
+                sync_queue.call_method0("get_nowait")?.extract::<&str>()?,
+                "event"
+            );
+            let async_queue = locals.get_item("async_queue").unwrap();
+            assert!(matches!(
+                async_queue.extract::<Consumer>()?,
+                Consumer::Queue { .. }
+            ));
+            Ok(())
+        })
+        .unwrap();
+    }
+}
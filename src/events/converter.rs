@@ -1,20 +1,17 @@
-use pyo3::prelude::*;
-use pyo3::types::PyDict;
 use ricq::client::event as rce;
 use ricq::handler::QEvent;
 
-use super::MessageSource;
-use crate::client::structs::Friend;
-use crate::dict_obj;
-use crate::exc::MapPyErr;
-use crate::message::convert::{serialize_as_py_chain, serialize_audio};
-use crate::utils::{datetime_from_ts, py_try, timedelta_from_secs};
+use super::raw::{RawChain, RawEvent, RawPtt};
 
-type PyDictRet = PyResult<Py<PyDict>>;
+type RawRet = Option<RawEvent>;
 
-pub async fn convert(event: QEvent) -> PyDictRet {
+/// 提取 RICQ 事件的原始数据，无需投递的事件返回 `None`。
+///
+/// 不会发起网络请求，语音事件的链接需要通过 `PlumbingClient.get_audio_url` 按需获取。
+/// 之后可以通过 [`RawEvent::into_event`] 转换为事件。
+pub async fn capture(event: QEvent) -> RawRet {
     match event {
-        QEvent::Login(_) => dict_obj! {},
+        QEvent::Login(_) => None,
         QEvent::GroupMessage(event) => handle_group_message(event).await,
         QEvent::GroupAudioMessage(event) => handle_group_audio(event).await,
         QEvent::FriendMessage(event) => handle_friend_message(event).await,
@@ -35,249 +32,234 @@ pub async fn convert(event: QEvent) -> PyDictRet {
         QEvent::GroupRequest(event) => handle_group_request(event),
         QEvent::SelfInvited(event) => handle_group_invitation(event),
         QEvent::NewFriendRequest(event) => handle_friend_request(event),
-        unknown => dict_obj!(type_name: "UnknownEvent", internal_repr: format!("{:?}", unknown)),
+        QEvent::KickedOffline(event) => handle_kicked_offline(event),
+        QEvent::MSFOffline(event) => handle_msf_offline(event),
+        unknown => Some(RawEvent::Unknown {
+            repr: format!("{:?}", unknown),
+        }),
     }
 }
 
-async fn handle_group_message(event: rce::GroupMessageEvent) -> PyDictRet {
+async fn handle_group_message(event: rce::GroupMessageEvent) -> RawRet {
     let msg = event.inner;
-
-    let content = py_try(|py| serialize_as_py_chain(py, msg.elements))?;
-    dict_obj! {py !
-        type_name: "GroupMessage",
-        source: MessageSource::new(py, &msg.seqs, &msg.rands, msg.time)?,
-        content: content,
-        group: msg.group_code,
-        sender: msg.from_uin,
-    }
+    Some(RawEvent::GroupMessage {
+        seqs: msg.seqs,
+        rands: msg.rands,
+        time: msg.time,
+        group_code: msg.group_code,
+        group_name: msg.group_name,
+        group_card: msg.group_card,
+        from_uin: msg.from_uin,
+        elements: RawChain(msg.elements),
+    })
 }
 
-async fn handle_group_recall(event: rce::GroupMessageRecallEvent) -> PyDictRet {
+async fn handle_group_recall(event: rce::GroupMessageRecallEvent) -> RawRet {
     let event = event.inner;
-    let time = py_try(|py| Ok(datetime_from_ts(py, event.time)?.into_py(py)))?;
-    dict_obj! {
-        type_name: "GroupRecallMessage",
-        time: time,
-        group: event.group_code,
-        author: event.author_uin,
-        operator: event.operator_uin,
-        seq: event.msg_seq,
-    }
+    Some(RawEvent::GroupMessageRecall {
+        time: event.time as i64,
+        group_code: event.group_code,
+        author_uin: event.author_uin,
+        operator_uin: event.operator_uin,
+        msg_seq: event.msg_seq,
+    })
 }
 
-async fn handle_group_audio(event: rce::GroupAudioMessageEvent) -> PyDictRet {
-    let url = event.url().await.py_res()?;
+async fn handle_group_audio(event: rce::GroupAudioMessageEvent) -> RawRet {
     let msg = event.inner;
-    let content = py_try(|py| serialize_audio(py, url, &msg.audio.0))?;
-    dict_obj! {py !
-        type_name: "GroupMessage",
-        source: MessageSource::new(py, &msg.seqs, &msg.rands, msg.time)?,
-        content: content,
-        group: msg.group_code,
-        sender: msg.from_uin,
-    }
+    Some(RawEvent::GroupAudioMessage {
+        seqs: msg.seqs,
+        rands: msg.rands,
+        time: msg.time,
+        group_code: msg.group_code,
+        group_name: msg.group_name,
+        group_card: msg.group_card,
+        from_uin: msg.from_uin,
+        audio: RawPtt(msg.audio.0),
+    })
 }
 
-async fn handle_friend_message(event: rce::FriendMessageEvent) -> PyDictRet {
+async fn handle_friend_message(event: rce::FriendMessageEvent) -> RawRet {
     let msg = event.inner;
-    let content = py_try(|py| serialize_as_py_chain(py, msg.elements))?;
-    dict_obj! {py !
-        type_name: "FriendMessage",
-        source: MessageSource::new(py, &msg.seqs, &msg.rands, msg.time)?,
-        content: content,
-        sender: msg.from_uin,
-    }
+    Some(RawEvent::FriendMessage {
+        seqs: msg.seqs,
+        rands: msg.rands,
+        time: msg.time,
+        from_uin: msg.from_uin,
+        from_nick: msg.from_nick,
+        target: msg.target,
+        elements: RawChain(msg.elements),
+    })
 }
 
-async fn handle_friend_recall(event: rce::FriendMessageRecallEvent) -> PyDictRet {
+async fn handle_friend_recall(event: rce::FriendMessageRecallEvent) -> RawRet {
     let event = event.inner;
-    let time = py_try(|py| Ok(datetime_from_ts(py, event.time)?.into_py(py)))?;
-    dict_obj! {
-        type_name: "FriendRecallMessage",
-        time: time,
-        author: event.friend_uin,
-        seq: event.msg_seq,
-    }
+    Some(RawEvent::FriendMessageRecall {
+        time: event.time as i64,
+        friend_uin: event.friend_uin,
+        msg_seq: event.msg_seq,
+    })
 }
 
-async fn handle_friend_audio(event: rce::FriendAudioMessageEvent) -> PyDictRet {
-    let url = event.url().await.py_res()?;
+async fn handle_friend_audio(event: rce::FriendAudioMessageEvent) -> RawRet {
     let msg = event.inner;
-    let content = py_try(|py| serialize_audio(py, url, &msg.audio.0))?;
-    dict_obj! {py !
-        type_name: "FriendMessage",
-        source: MessageSource::new(py, &msg.seqs, &msg.rands, msg.time)?,
-        content: content,
-        sender: msg.from_uin,
-    }
+    Some(RawEvent::FriendAudioMessage {
+        seqs: msg.seqs,
+        rands: msg.rands,
+        time: msg.time,
+        from_uin: msg.from_uin,
+        from_nick: msg.from_nick,
+        target: msg.target,
+        audio: RawPtt(msg.audio.0),
+    })
 }
 
-async fn handle_temp_message(event: rce::GroupTempMessageEvent) -> PyDictRet {
+async fn handle_temp_message(event: rce::GroupTempMessageEvent) -> RawRet {
     let msg = event.inner;
-    let content = py_try(|py| serialize_as_py_chain(py, msg.elements))?;
-
-
-    dict_obj! {py !
-        type_name: "TempMessage",
-        source: MessageSource::new(py, &msg.seqs, &msg.rands, msg.time)?,
-        content: content,
-        group: msg.group_code,
-        sender: msg.from_uin,
-    }
+    Some(RawEvent::GroupTempMessage {
+        seqs: msg.seqs,
+        rands: msg.rands,
+        time: msg.time,
+        group_code: msg.group_code,
+        from_uin: msg.from_uin,
+        from_nick: msg.from_nick,
+        elements: RawChain(msg.elements),
+    })
 }
 
-async fn handle_group_nudge(event: rce::GroupPokeEvent) -> PyDictRet {
+async fn handle_group_nudge(event: rce::GroupPokeEvent) -> RawRet {
     let event = event.inner;
-
-    dict_obj! {
-        type_name: "GroupNudge",
-        group: event.group_code,
+    Some(RawEvent::GroupPoke {
+        group_code: event.group_code,
         sender: event.sender,
         receiver: event.receiver,
-    }
+    })
 }
 
-async fn handle_friend_nudge(event: rce::FriendPokeEvent) -> PyDictRet {
+async fn handle_friend_nudge(event: rce::FriendPokeEvent) -> RawRet {
     let client = event.client;
     if client.uin().await == event.inner.sender {
-        return dict_obj! {};
-    }
-    let event = event.inner;
-    dict_obj! {
-        type_name: "FriendNudge",
-        sender: event.sender,
+        return None;
     }
+    Some(RawEvent::FriendPoke {
+        sender: event.inner.sender,
+    })
 }
 
-fn handle_new_friend(event: rce::NewFriendEvent) -> PyDictRet {
-    let friend: Friend = event.inner.into();
-    dict_obj! {
-        type_name: "NewFriend",
-        friend: friend,
-    }
+fn handle_new_friend(event: rce::NewFriendEvent) -> RawRet {
+    Some(RawEvent::NewFriend {
+        friend: event.inner.into(),
+    })
 }
-async fn handle_new_member(event: rce::NewMemberEvent) -> PyDictRet {
+
+async fn handle_new_member(event: rce::NewMemberEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {
-        type_name: "NewMember",
-        group: event.group_code,
-        member: event.member_uin,
-    }
+    Some(RawEvent::NewMember {
+        group_code: event.group_code,
+        member_uin: event.member_uin,
+    })
 }
 
-async fn handle_group_leave(event: rce::GroupLeaveEvent) -> PyDictRet {
+async fn handle_group_leave(event: rce::GroupLeaveEvent) -> RawRet {
     let event = event.inner;
-
-    dict_obj! {
-        type_name: "MemberLeaveGroup",
-        group_uin: event.group_code,
+    Some(RawEvent::GroupLeave {
+        group_code: event.group_code,
         member_uin: event.member_uin,
-    }
+        operator_uin: event.operator_uin,
+    })
 }
 
-async fn handle_group_disband(event: rce::GroupDisbandEvent) -> PyDictRet {
+async fn handle_group_disband(event: rce::GroupDisbandEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {
-        type_name: "GroupDisband",
-        group_uin: event.group_code,
+    Some(RawEvent::GroupDisband {
+        group_code: event.group_code,
         operator_uin: event.operator_uin,
-    }
+    })
 }
 
-async fn handle_friend_delete(event: rce::DeleteFriendEvent) -> PyDictRet {
-    dict_obj! {
-        type_name: "FriendDeleted",
-        friend_uin: event.inner.uin,
-    }
+async fn handle_friend_delete(event: rce::DeleteFriendEvent) -> RawRet {
+    Some(RawEvent::DeleteFriend {
+        uin: event.inner.uin,
+    })
 }
 
-async fn handle_mute(event: rce::GroupMuteEvent) -> PyDictRet {
+async fn handle_mute(event: rce::GroupMuteEvent) -> RawRet {
     let event = event.inner;
-
-    if event.target_uin == 0 {
-        return dict_obj! {
-            type_name: "GroupMute",
-            group: event.group_code,
-            operator: event.operator_uin,
-            status: event.duration.as_secs() == 0
-        };
-    }
-    let duration = event.duration.as_secs();
-    let duration = py_try(|py| {
-        Ok(if duration != 0 {
-            timedelta_from_secs(py, duration)?.into_py(py)
-        } else {
-            false.into_py(py)
-        })
-    })?;
-    dict_obj! {
-        type_name: "MemberMute",
-        group: event.group_code,
-        operator: event.operator_uin,
-        target: event.target_uin,
-        duration: duration,
-    }
+    Some(RawEvent::GroupMute {
+        group_code: event.group_code,
+        operator_uin: event.operator_uin,
+        target_uin: event.target_uin,
+        duration: event.duration.as_secs(),
+    })
 }
 
-async fn handle_permission_change(event: rce::MemberPermissionChangeEvent) -> PyDictRet {
+async fn handle_permission_change(event: rce::MemberPermissionChangeEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {
-        type_name: "MemberPermissionChange",
-        group: event.group_code,
-        target: event.member_uin,
-        permission: event.new_permission as u8,
-    }
+    Some(RawEvent::MemberPermissionChange {
+        group_code: event.group_code,
+        member_uin: event.member_uin,
+        new_permission: event.new_permission as u8,
+    })
 }
 
-async fn handle_group_info_update(event: rce::GroupNameUpdateEvent) -> PyDictRet {
+async fn handle_group_info_update(event: rce::GroupNameUpdateEvent) -> RawRet {
     let event = event.inner;
-    let info: Py<PyDict> = dict_obj! {
-        name: event.group_name
-    }?;
-    dict_obj! {
-        type_name: "GroupInfoUpdate",
-        group: event.group_code,
-        operator: event.operator_uin,
-        info: info,
-    }
+    Some(RawEvent::GroupNameUpdate {
+        group_code: event.group_code,
+        operator_uin: event.operator_uin,
+        group_name: event.group_name,
+    })
 }
 
-fn handle_group_request(event: rce::JoinGroupRequestEvent) -> PyDictRet {
+fn handle_group_request(event: rce::JoinGroupRequestEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {py !
-        type_name: "JoinGroupRequest",
-        seq: event.msg_seq,
-        time: datetime_from_ts(py, event.msg_time).map(|v| v.into_py(py))?,
-        group_uin: event.group_code,
+    Some(RawEvent::GroupRequest {
+        msg_seq: event.msg_seq,
+        msg_time: event.msg_time as i64,
+        group_code: event.group_code,
         group_name: event.group_name,
-        request_uin: event.req_uin,
-        request_nickname: event.req_nick,
+        req_uin: event.req_uin,
+        req_nick: event.req_nick,
         suspicious: event.suspicious,
         invitor_uin: event.invitor_uin,
-        invitor_nickname: event.invitor_nick,
-    }
+        invitor_nick: event.invitor_nick,
+    })
 }
 
-fn handle_group_invitation(event: rce::SelfInvitedEvent) -> PyDictRet {
+fn handle_group_invitation(event: rce::SelfInvitedEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {py !
-        type_name: "JoinGroupInvitation",
-        seq: event.msg_seq,
-        time: datetime_from_ts(py, event.msg_time).map(|v| v.into_py(py))?,
-        group_uin: event.group_code,
+    Some(RawEvent::SelfInvited {
+        msg_seq: event.msg_seq,
+        msg_time: event.msg_time as i64,
+        group_code: event.group_code,
         group_name: event.group_name,
         invitor_uin: event.invitor_uin,
-        invitor_nickname: event.invitor_nick,
-    }
+        invitor_nick: event.invitor_nick,
+    })
 }
 
-fn handle_friend_request(event: rce::NewFriendRequestEvent) -> PyDictRet {
+fn handle_friend_request(event: rce::NewFriendRequestEvent) -> RawRet {
     let event = event.inner;
-    dict_obj! {
-        type_name: "NewFriendRequest",
-        seq: event.msg_seq,
-        uin: event.req_uin,
-        nickname: event.req_nick,
+    Some(RawEvent::NewFriendRequest {
+        msg_seq: event.msg_seq,
+        req_uin: event.req_uin,
+        req_nick: event.req_nick,
         message: event.message,
-    }
+    })
+}
+
+fn handle_kicked_offline(event: rce::KickedOfflineEvent) -> RawRet {
+    let event = event.inner;
+    Some(RawEvent::KickedOffline {
+        title: event.title,
+        tips: event.tips,
+    })
+}
+
+fn handle_msf_offline(event: rce::MSFOfflineEvent) -> RawRet {
+    let event = event.inner;
+    Some(RawEvent::MSFOffline {
+        title: event.title,
+        info: event.info,
+    })
 }
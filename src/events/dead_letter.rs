@@ -0,0 +1,113 @@
+//! 投递失败的事件。
+//!
+//! 事件在转换、获取客户端引用或调用队列的 `put` 时失败后，会被包装为 [`FailedDelivery`] 交给死信队列，
+//! 同时按事件类型记录失败次数。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use pyo3_asyncio::{into_future_with_locals, TaskLocals};
+use pyo3_repr::PyRepr;
+
+use crate::utils::{py_try, py_use};
+
+/// 投递失败的阶段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// 转换为事件或 Python 事件对象
+    Conversion,
+    /// 获取客户端引用
+    ClientLookup,
+    /// 调用队列的 `put`
+    Put,
+    /// 有序投递超时
+    Timeout,
+}
+
+impl Stage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Stage::Conversion => "conversion",
+            Stage::ClientLookup => "client_lookup",
+            Stage::Put => "put",
+            Stage::Timeout => "timeout",
+        }
+    }
+}
+
+/// 投递失败的事件。
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct FailedDelivery {
+    pub stage: String,
+    pub type_name: String,
+    /// 已转换的事件对象，转换前失败时为 `None`
+    pub event: PyObject,
+    pub event_repr: String,
+    pub error: PyObject,
+}
+
+/// 死信队列与失败计数。
+pub struct DeadLetters {
+    sink: Option<PyObject>,
+    locals: TaskLocals,
+    errors: Mutex<HashMap<String, u64>>,
+}
+
+impl DeadLetters {
+    pub fn new(sink: Option<PyObject>, locals: TaskLocals) -> Self {
+        Self {
+            sink,
+            locals,
+            errors: Mutex::default(),
+        }
+    }
+
+    /// 各事件类型的投递失败次数。
+    pub fn errors(&self) -> HashMap<String, u64> {
+        self.errors
+            .lock()
+            .map(|errors| errors.clone())
+            .unwrap_or_default()
+    }
+
+    /// 记录投递失败，存在死信队列时将失败信息交给死信队列，否则打印异常。
+    pub fn report(
+        &self,
+        stage: Stage,
+        type_name: &str,
+        event_repr: &str,
+        event: Option<PyObject>,
+        error: PyErr,
+    ) {
+        tracing::error!("事件投递失败 ({}): {}", stage.as_str(), event_repr);
+        if let Ok(mut errors) = self.errors.lock() {
+            *errors.entry(type_name.to_owned()).or_default() += 1;
+        }
+        let Some(sink) = &self.sink else {
+            py_use(|py| error.print_and_set_sys_last_vars(py));
+            return;
+        };
+        let res = py_try(|py| {
+            let failed = FailedDelivery {
+                stage: stage.as_str().into(),
+                type_name: type_name.into(),
+                event: event.unwrap_or_else(|| py.None()),
+                event_repr: event_repr.into(),
+                error: error.value(py).into_py(py),
+            };
+            into_future_with_locals(
+                &self.locals,
+                sink.as_ref(py).getattr("put")?.call1((failed,))?,
+            )
+        });
+        let event_repr = event_repr.to_owned();
+        tokio::spawn(async move {
+            if let Err(e) = async { res?.await }.await {
+                tracing::error!("死信队列处理失败: {}", event_repr);
+                py_use(|py| e.print_and_set_sys_last_vars(py));
+            }
+        });
+    }
+}
@@ -0,0 +1,105 @@
+//! 重复事件的过滤。
+//!
+//! 重连后服务器可能会重新推送最近的消息，这里按消息的会话、序号、随机数与时间记录最近处理过的事件。
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru_time_cache::LruCache;
+
+use super::model::Event;
+
+const DEDUP_CAPACITY: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DedupKey {
+    type_name: &'static str,
+    context: i64,
+    seq: i64,
+    rand: i64,
+    time: i64,
+}
+
+impl DedupKey {
+    fn of(event: &Event) -> Option<Self> {
+        let type_name = event.type_name();
+        let key = |context, seq, rand, time| {
+            Some(Self {
+                type_name,
+                context,
+                seq,
+                rand,
+                time,
+            })
+        };
+        match event {
+            Event::GroupMessage { source, group, .. }
+            | Event::SelfGroupMessage { source, group, .. } => key(
+                *group,
+                source.seqs.first().copied()?.into(),
+                source.rands.first().copied()?.into(),
+                source.time.into(),
+            ),
+            Event::FriendMessage { source, sender, .. }
+            | Event::TempMessage { source, sender, .. }
+            | Event::SelfFriendMessage {
+                source,
+                target: sender,
+                ..
+            } => key(
+                *sender,
+                source.seqs.first().copied()?.into(),
+                source.rands.first().copied()?.into(),
+                source.time.into(),
+            ),
+            Event::GroupRecallMessage {
+                group, seq, time, ..
+            } => key(*group, (*seq).into(), 0, *time),
+            Event::FriendRecallMessage {
+                author, seq, time, ..
+            } => key(*author, (*seq).into(), 0, *time),
+            Event::NewFriendRequest { seq, uin, .. } => key(*uin, *seq, 0, 0),
+            Event::JoinGroupRequest {
+                seq,
+                time,
+                group_uin,
+                request_uin,
+                ..
+            } => key(*group_uin, *seq, *request_uin, *time),
+            Event::JoinGroupInvitation {
+                seq,
+                time,
+                group_uin,
+                ..
+            } => key(*group_uin, *seq, 0, *time),
+            _ => None,
+        }
+    }
+}
+
+/// 最近处理过的消息、撤回与申请事件。
+pub struct EventDeduper {
+    seen: Mutex<LruCache<DedupKey, ()>>,
+}
+
+impl EventDeduper {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            seen: Mutex::new(LruCache::with_expiry_duration_and_capacity(
+                window,
+                DEDUP_CAPACITY,
+            )),
+        }
+    }
+
+    /// 记录事件，并返回事件在时间窗口内是否已经出现过。
+    pub fn is_duplicate(&self, event: &Event) -> bool {
+        let Some(key) = DedupKey::of(event) else {
+            return false;
+        };
+        let Ok(mut seen) = self.seen.lock() else {
+            return false;
+        };
+        seen.insert(key, ()).is_some()
+    }
+}
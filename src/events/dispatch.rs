@@ -0,0 +1,260 @@
+//! 有序、带背压的事件投递。
+//!
+//! 每个使用 [`DispatchPolicy`] 的队列拥有若干个投递任务，每个任务持有一个有界缓冲区并按顺序调用 `put`。
+//! 同一会话（群或用户）的事件总是进入同一个缓冲区，因此会话内的投递顺序与接收顺序一致。
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use pyo3::exceptions::{PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3_asyncio::TaskLocals;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::consumer::Consumer;
+use super::dead_letter::{DeadLetters, Stage};
+use super::deliver;
+use super::model::Event;
+use crate::utils::py_use;
+
+/// 缓冲区已满时的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// 等待缓冲区有空位，会阻塞事件接收
+    Block,
+    /// 丢弃缓冲区中最早的事件
+    DropOldest,
+    /// 丢弃新到达的事件
+    DropNewest,
+}
+
+#[derive(Debug, Default)]
+struct DispatchStats {
+    dropped: AtomicU64,
+    timed_out: AtomicU64,
+}
+
+/// 有序投递策略。
+#[pyclass(module = "ichika.core")]
+#[derive(Debug, Clone)]
+pub struct DispatchPolicy {
+    capacity: usize,
+    overflow: Overflow,
+    timeout: Option<Duration>,
+    workers: usize,
+    stats: Arc<DispatchStats>,
+}
+
+#[pymethods]
+impl DispatchPolicy {
+    #[new]
+    #[pyo3(signature = (*, capacity = 256, overflow = "block", timeout = None, workers = 1))]
+    pub fn new(
+        capacity: usize,
+        overflow: &str,
+        timeout: Option<f64>,
+        workers: usize,
+    ) -> PyResult<Self> {
+        let overflow = match overflow {
+            "block" => Overflow::Block,
+            "drop_oldest" => Overflow::DropOldest,
+            "drop_newest" => Overflow::DropNewest,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "未知的溢出策略: {other}，可选值为 block, drop_oldest, drop_newest"
+                )))
+            }
+        };
+        if capacity == 0 || workers == 0 {
+            return Err(PyValueError::new_err("capacity 与 workers 必须大于 0"));
+        }
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(format!("无效的超时时间: {e}")))?;
+        Ok(Self {
+            capacity,
+            overflow,
+            timeout,
+            workers,
+            stats: Arc::default(),
+        })
+    }
+
+    /// 因缓冲区已满而被丢弃的事件数
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.stats.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 投递超时的事件数
+    #[getter]
+    fn timed_out(&self) -> u64 {
+        self.stats.timed_out.load(Ordering::Relaxed)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DispatchPolicy(capacity={}, overflow={:?}, timeout={:?}, workers={}, dropped={}, timed_out={})",
+            self.capacity,
+            self.overflow,
+            self.timeout,
+            self.workers,
+            self.dropped(),
+            self.timed_out()
+        )
+    }
+}
+
+struct Buffer {
+    events: Mutex<VecDeque<Event>>,
+    capacity: usize,
+    overflow: Overflow,
+    item: Notify,
+    space: Notify,
+    stats: Arc<DispatchStats>,
+}
+
+impl Buffer {
+    fn new(policy: &DispatchPolicy) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::with_capacity(policy.capacity)),
+            capacity: policy.capacity,
+            overflow: policy.overflow,
+            item: Notify::new(),
+            space: Notify::new(),
+            stats: policy.stats.clone(),
+        }
+    }
+
+    async fn push(&self, event: Event) {
+        loop {
+            {
+                let mut events = self.events.lock().unwrap();
+                if events.len() < self.capacity {
+                    events.push_back(event);
+                    self.item.notify_one();
+                    return;
+                }
+                match self.overflow {
+                    Overflow::Block => {}
+                    Overflow::DropOldest => {
+                        let dropped = events.pop_front();
+                        events.push_back(event);
+                        self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!("事件缓冲区已满, 丢弃最早的事件: {:?}", dropped);
+                        return;
+                    }
+                    Overflow::DropNewest => {
+                        self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!("事件缓冲区已满, 丢弃新事件: {:?}", event);
+                        return;
+                    }
+                }
+            }
+            self.space.notified().await;
+        }
+    }
+
+    async fn pop(&self) -> Event {
+        loop {
+            if let Some(event) = self.events.lock().unwrap().pop_front() {
+                self.space.notify_one();
+                return event;
+            }
+            self.item.notified().await;
+        }
+    }
+}
+
+/// 使用 [`DispatchPolicy`] 的队列的投递状态，投递任务在首次收到事件时启动，在队列被移除时停止。
+pub struct OrderedDispatch {
+    policy: DispatchPolicy,
+    buffers: OnceCell<Vec<(Arc<Buffer>, JoinHandle<()>)>>,
+}
+
+impl Drop for OrderedDispatch {
+    fn drop(&mut self) {
+        for (_, task) in self.buffers.get().into_iter().flatten() {
+            task.abort();
+        }
+    }
+}
+
+impl OrderedDispatch {
+    pub fn new(policy: DispatchPolicy) -> Self {
+        Self {
+            policy,
+            buffers: OnceCell::new(),
+        }
+    }
+
+    pub async fn push(
+        &self,
+        consumer: &Consumer,
+        locals: &TaskLocals,
+        uin: i64,
+        dead_letters: &Arc<DeadLetters>,
+        event: Event,
+    ) {
+        let buffers = self.buffers.get_or_init(|| {
+            (0..self.policy.workers)
+                .map(|_| {
+                    let buffer = Arc::new(Buffer::new(&self.policy));
+                    let task = tokio::spawn(run(
+                        buffer.clone(),
+                        py_use(|py| consumer.clone_ref(py)),
+                        locals.clone(),
+                        uin,
+                        dead_letters.clone(),
+                        self.policy.timeout,
+                    ));
+                    (buffer, task)
+                })
+                .collect()
+        });
+        let mut hasher = DefaultHasher::new();
+        event.group().or_else(|| event.user()).hash(&mut hasher);
+        let index = hasher.finish() as usize % buffers.len();
+        buffers[index].0.push(event).await;
+    }
+}
+
+async fn run(
+    buffer: Arc<Buffer>,
+    consumer: Consumer,
+    locals: TaskLocals,
+    uin: i64,
+    dead_letters: Arc<DeadLetters>,
+    timeout: Option<Duration>,
+) {
+    loop {
+        let event = buffer.pop().await;
+        let type_name = event.type_name();
+        let delivery = deliver(&consumer, &locals, uin, &event);
+        let res = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, delivery).await {
+                Ok(res) => res,
+                Err(_) => {
+                    buffer.stats.timed_out.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("事件投递超时: {:?}", event);
+                    Err((
+                        Stage::Timeout,
+                        None,
+                        PyTimeoutError::new_err(format!("事件投递超过 {timeout:?}")),
+                    ))
+                }
+            },
+            None => delivery.await,
+        };
+        if let Err((stage, py_event, err)) = res {
+            dead_letters.report(stage, type_name, &format!("{event:?}"), py_event, err);
+        }
+    }
+}
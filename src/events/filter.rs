@@ -0,0 +1,141 @@
+//! 事件队列的过滤器。
+//!
+//! 过滤器在 Rust 侧对 [`Event`] 求值，未通过过滤的事件不会被转换为 Python 对象。
+
+use std::collections::HashSet;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use regex::Regex;
+
+use super::model::Event;
+
+/// 事件过滤器，所有条件均满足时事件才会投递给对应的队列。
+#[pyclass(module = "ichika.core")]
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    types: Option<HashSet<String>>,
+    groups: Option<HashSet<i64>>,
+    exclude_groups: HashSet<i64>,
+    users: Option<HashSet<i64>>,
+    exclude_users: HashSet<i64>,
+    ignore_self: bool,
+    ignore_client_sent: bool,
+    prefix: Option<String>,
+    pattern: Option<Regex>,
+}
+
+#[pymethods]
+impl EventFilter {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        types = None,
+        groups = None,
+        exclude_groups = None,
+        users = None,
+        exclude_users = None,
+        ignore_self = false,
+        ignore_client_sent = false,
+        prefix = None,
+        pattern = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        types: Option<HashSet<String>>,
+        groups: Option<HashSet<i64>>,
+        exclude_groups: Option<HashSet<i64>>,
+        users: Option<HashSet<i64>>,
+        exclude_users: Option<HashSet<i64>>,
+        ignore_self: bool,
+        ignore_client_sent: bool,
+        prefix: Option<String>,
+        pattern: Option<&str>,
+    ) -> PyResult<Self> {
+        if let Some(types) = &types {
+            if let Some(unknown) = types
+                .iter()
+                .find(|name| !Event::TYPE_NAMES.contains(&name.as_str()))
+            {
+                return Err(PyValueError::new_err(format!("未知的事件类型: {unknown}")));
+            }
+        }
+        let pattern = pattern
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(format!("无效的正则表达式: {e}")))?;
+        Ok(Self {
+            types,
+            groups,
+            exclude_groups: exclude_groups.unwrap_or_default(),
+            users,
+            exclude_users: exclude_users.unwrap_or_default(),
+            ignore_self,
+            ignore_client_sent,
+            prefix,
+            pattern,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl EventFilter {
+    /// 检查事件是否通过过滤。
+    ///
+    /// 群与用户条件只作用于带有对应信息的事件，文本条件会排除所有非消息事件。
+    pub fn matches(&self, event: &Event, self_uin: i64) -> bool {
+        if let Some(types) = &self.types && !types.contains(event.type_name()) {
+            return false;
+        }
+        if self.ignore_client_sent
+            && matches!(
+                event,
+                Event::SelfGroupMessage {
+                    from_client: true,
+                    ..
+                } | Event::SelfFriendMessage {
+                    from_client: true,
+                    ..
+                }
+            )
+        {
+            return false;
+        }
+        if let Some(group) = event.group() {
+            if self.exclude_groups.contains(&group) {
+                return false;
+            }
+            if let Some(groups) = &self.groups && !groups.contains(&group) {
+                return false;
+            }
+        }
+        if let Some(user) = event.user() {
+            if self.ignore_self && user == self_uin {
+                return false;
+            }
+            if self.exclude_users.contains(&user) {
+                return false;
+            }
+            if let Some(users) = &self.users && !users.contains(&user) {
+                return false;
+            }
+        }
+        if self.prefix.is_none() && self.pattern.is_none() {
+            return true;
+        }
+        let Some(content) = event.content() else {
+            return false;
+        };
+        let text = content.plain_text();
+        if let Some(prefix) = &self.prefix && !text.starts_with(prefix.as_str()) {
+            return false;
+        }
+        if let Some(pattern) = &self.pattern && !pattern.is_match(&text) {
+            return false;
+        }
+        true
+    }
+}
@@ -0,0 +1,180 @@
+//! 最近消息的缓存。
+//!
+//! 记录接收到的与当前客户端发送的群消息和好友消息，用于按消息元信息获取消息内容以及补全撤回事件的原消息内容。
+//! 群临时会话消息不会被记录。
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru_time_cache::LruCache;
+use pyo3::prelude::*;
+use pyo3_repr::PyRepr;
+
+use super::model::{Event, Source};
+use super::MessageSource;
+use crate::message::wire::WireChain;
+
+/// 缓存的消息。
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub group: bool,
+    pub target: i64,
+    pub sender: i64,
+    pub source: Source,
+    pub content: WireChain,
+}
+
+impl HistoryEntry {
+    /// 从群消息与好友消息事件构造，其他事件返回 `None`。
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let (group, target, sender, source, content) = match event {
+            Event::GroupMessage {
+                source,
+                content,
+                group,
+                sender,
+                ..
+            }
+            | Event::SelfGroupMessage {
+                source,
+                content,
+                group,
+                sender,
+                ..
+            } => (true, *group, *sender, source, content),
+            Event::FriendMessage {
+                source,
+                content,
+                sender,
+                ..
+            } => (false, *sender, *sender, source, content),
+            Event::SelfFriendMessage {
+                source,
+                content,
+                sender,
+                target,
+                ..
+            } => (false, *target, *sender, source, content),
+            _ => return None,
+        };
+        Some(Self {
+            group,
+            target,
+            sender,
+            source: source.clone(),
+            content: content.clone(),
+        })
+    }
+
+    pub fn kind(&self) -> &'static str {
+        if self.group {
+            "group"
+        } else {
+            "friend"
+        }
+    }
+
+    pub fn into_py_message(self, py: Python) -> PyResult<CachedMessage> {
+        Ok(CachedMessage {
+            kind: self.kind().into(),
+            target: self.target,
+            sender: self.sender,
+            source: self.source.into_py_source(py)?,
+            content: self.content.into_py_chain(py)?,
+        })
+    }
+}
+
+/// 缓存的消息。
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct CachedMessage {
+    pub kind: String,
+    /// 群号或好友 QQ 号
+    pub target: i64,
+    pub sender: i64,
+    pub source: MessageSource,
+    pub content: PyObject,
+}
+
+struct Inner {
+    /// `(是否为群消息, 群号或好友 QQ 号, 序列号, 随机序列号)` 到消息
+    messages: LruCache<(bool, i64, i32, i32), HistoryEntry>,
+    /// `(是否为群消息, 群号或好友 QQ 号, 序列号)` 到随机序列号，用于撤回事件
+    by_seq: LruCache<(bool, i64, i32), i32>,
+}
+
+pub struct MessageHistory {
+    inner: Mutex<Inner>,
+}
+
+impl Default for MessageHistory {
+    fn default() -> Self {
+        let ttl = Duration::from_secs(3600);
+        Self {
+            inner: Mutex::new(Inner {
+                messages: LruCache::with_expiry_duration_and_capacity(ttl, 8192),
+                by_seq: LruCache::with_expiry_duration_and_capacity(ttl, 8192),
+            }),
+        }
+    }
+}
+
+impl MessageHistory {
+    /// 记录消息。
+    pub fn insert(&self, entry: HistoryEntry) {
+        let (Some(&seq), Some(&rand)) = (entry.source.seqs.first(), entry.source.rands.first())
+        else {
+            return;
+        };
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.by_seq.insert((entry.group, entry.target, seq), rand);
+            inner
+                .messages
+                .insert((entry.group, entry.target, seq, rand), entry);
+        }
+    }
+
+    /// 按会话、序列号与随机序列号获取消息。
+    pub fn get(&self, group: bool, target: i64, seq: i32, rand: i32) -> Option<HistoryEntry> {
+        self.inner
+            .lock()
+            .ok()?
+            .messages
+            .get(&(group, target, seq, rand))
+            .cloned()
+    }
+
+    fn get_by_seq(&self, group: bool, target: i64, seq: i32) -> Option<HistoryEntry> {
+        let mut inner = self.inner.lock().ok()?;
+        let rand = *inner.by_seq.get(&(group, target, seq))?;
+        inner.messages.get(&(group, target, seq, rand)).cloned()
+    }
+
+    /// 补全撤回事件的原消息内容。
+    pub fn fill_recall(&self, event: &mut Event) {
+        match event {
+            Event::GroupRecallMessage {
+                group,
+                seq,
+                content,
+                ..
+            } => {
+                if content.is_none() {
+                    *content = self.get_by_seq(true, *group, *seq).map(|e| e.content);
+                }
+            }
+            Event::FriendRecallMessage {
+                author,
+                seq,
+                content,
+                ..
+            } => {
+                if content.is_none() {
+                    *content = self.get_by_seq(false, *author, *seq).map(|e| e.content);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,16 +1,50 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
 use async_trait::async_trait;
-use pyo3::exceptions::PyIndexError;
+use pyo3::exceptions::{PyIndexError, PyTypeError};
 use pyo3::prelude::*;
 use pyo3::types::*;
-use pyo3_asyncio::{into_future_with_locals, TaskLocals};
+use pyo3_asyncio::TaskLocals;
 use pyo3_repr::PyRepr;
 use ricq::client::event::DisconnectReason;
 use ricq::client::NetworkStatus;
 use ricq::handler::{Handler, QEvent};
+use ricq::structs::MessageReceipt;
 
+pub mod archive;
+pub mod consumer;
 pub mod converter;
+pub mod dead_letter;
+pub mod dedup;
+pub mod dispatch;
+pub mod filter;
+pub mod history;
+pub mod model;
+pub mod raw;
+pub mod record;
+pub mod roles;
+pub mod roster;
+pub mod sent;
+pub mod structs;
+pub mod waiter;
 
-use crate::utils::{datetime_from_ts, py_client_refs, py_try, py_use};
+use self::archive::MessageArchive;
+use self::consumer::{is_event_loop, Consumer};
+use self::dead_letter::{DeadLetters, Stage};
+use self::dedup::EventDeduper;
+use self::dispatch::{DispatchPolicy, OrderedDispatch};
+use self::filter::EventFilter;
+use self::history::{HistoryEntry, MessageHistory};
+use self::model::{Event, Source};
+use self::record::EventRecorder;
+use self::roles::MemberRoles;
+use self::roster::RosterSnapshot;
+use self::sent::SentMessages;
+use self::waiter::Waiters;
+use crate::message::wire::WireChain;
+use crate::utils::{datetime_from_ts, py_client_refs, py_use};
 
 #[pyclass(get_all, module = "ichika.core")]
 #[derive(PyRepr, Clone)]
@@ -40,106 +74,434 @@ impl MessageSource {
     }
 }
 
-pub struct PyHandler {
-    queues: Py<PyList>,
+/// 消息发送者的信息。
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct MessageSender {
+    pub uin: i64,
+    pub nickname: Option<String>,
+    pub card_name: Option<String>,
+    pub role: PyObject,
+}
+
+#[pymethods]
+impl MessageSender {
+    /// 显示名称，依次使用群名片、昵称与 QQ 号。
+    #[getter]
+    fn name(&self) -> String {
+        [&self.card_name, &self.nickname]
+            .into_iter()
+            .flatten()
+            .find(|name| !name.is_empty())
+            .cloned()
+            .unwrap_or_else(|| self.uin.to_string())
+    }
+}
+
+/// 事件队列及其过滤器与投递策略。
+///
+/// 从 Python 侧传入时可以是单独的队列，也可以是 `(队列, EventFilter, DispatchPolicy, 事件循环)` 元组，其中各选项均可省略。
+pub struct Subscriber {
+    consumer: Consumer,
+    filter: Option<EventFilter>,
+    ordered: Option<OrderedDispatch>,
+}
+
+impl Subscriber {
+    pub fn set_filter(&mut self, filter: EventFilter) {
+        self.filter = Some(filter);
+    }
+
+    fn accepts(&self, event: &Event, self_uin: i64) -> bool {
+        self.filter
+            .as_ref()
+            .map_or(true, |filter| filter.matches(event, self_uin))
+    }
+}
+
+/// 事件队列的订阅，可以用于取消订阅。
+///
+/// 由 `PlumbingClient.subscribe` 返回，登录时传入的事件队列的订阅可以通过 `PlumbingClient.subscriptions` 获取。
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct Subscription {
+    pub uin: i64,
+    pub id: u64,
+}
+
+impl<'py> FromPyObject<'py> for Subscriber {
+    fn extract(ob: &'py PyAny) -> PyResult<Self> {
+        let Ok(items) = ob.downcast::<PyTuple>() else {
+            return Ok(Self {
+                consumer: ob.extract()?,
+                filter: None,
+                ordered: None,
+            });
+        };
+        let mut sub = Self {
+            consumer: items.get_item(0)?.extract()?,
+            filter: None,
+            ordered: None,
+        };
+        for opt in items.iter().skip(1) {
+            if let Ok(filter) = opt.extract::<EventFilter>() {
+                sub.filter = Some(filter);
+            } else if let Ok(policy) = opt.extract::<DispatchPolicy>() {
+                sub.ordered = Some(OrderedDispatch::new(policy));
+            } else if is_event_loop(opt)? {
+                sub.consumer.set_event_loop(opt)?;
+            } else {
+                return Err(PyTypeError::new_err(format!(
+                    "事件队列选项必须为 EventFilter、DispatchPolicy 或事件循环: {opt}"
+                )));
+            }
+        }
+        Ok(sub)
+    }
+}
+
+/// 投递失败的阶段、已转换的事件对象与异常。
+type Failure = (Stage, Option<PyObject>, PyErr);
+
+/// 将事件转换为 Python 对象并交给消费者。
+async fn deliver(
+    consumer: &Consumer,
+    locals: &TaskLocals,
+    uin: i64,
+    event: &Event,
+) -> Result<(), Failure> {
+    let py_event = Python::with_gil(|py| -> Result<_, Failure> {
+        let client = py_client_refs(py)
+            .get_item(uin)
+            .map_err(|e| (Stage::ClientLookup, None, e))?;
+        event
+            .clone()
+            .into_py_event(py, client.into_py(py))
+            .map_err(|e| (Stage::Conversion, None, e))
+    })?;
+    consumer
+        .put(locals, py_use(|py| py_event.clone_ref(py)))
+        .await
+        .map_err(|e| (Stage::Put, Some(py_event), e))
+}
+
+/// 事件的分发状态，由 [`PyHandler`] 与客户端共享，客户端可以借此投递连接状态事件。
+pub struct EventDispatcher {
+    subscribers: RwLock<Vec<(u64, Arc<Subscriber>)>>,
+    next_subscriber: AtomicU64,
     locals: TaskLocals,
     uin: i64,
+    dedup: Option<EventDeduper>,
+    recorder: Option<EventRecorder>,
+    archive: Option<MessageArchive>,
+    dead_letters: Arc<DeadLetters>,
+    sent: SentMessages,
+    roles: MemberRoles,
+    roster: RosterSnapshot,
+    waiters: Waiters,
+    history: MessageHistory,
 }
 
-impl PyHandler {
-    pub fn new(queues: Py<PyList>, locals: TaskLocals, uin: i64) -> Self {
-        Self {
-            queues,
-            locals,
-            uin,
+impl EventDispatcher {
+    /// 投递失败的记录。
+    pub fn dead_letters(&self) -> &DeadLetters {
+        &self.dead_letters
+    }
+
+    /// 已知的群成员权限。
+    pub fn roles(&self) -> &MemberRoles {
+        &self.roles
+    }
+
+    /// 好友、群与群成员列表的快照。
+    pub fn roster(&self) -> &RosterSnapshot {
+        &self.roster
+    }
+
+    /// 最近的消息。
+    pub fn history(&self) -> &MessageHistory {
+        &self.history
+    }
+
+    /// 消息的本地存档。
+    pub fn archive(&self) -> Option<&MessageArchive> {
+        self.archive.as_ref()
+    }
+
+    /// 记录消息到最近消息的缓存与本地存档。
+    fn record_message(&self, entry: HistoryEntry) {
+        if let Some(archive) = &self.archive {
+            archive.insert(self.uin, entry.clone());
         }
+        self.history.insert(entry);
+    }
+
+    /// 记录当前客户端发送的消息。
+    pub fn record_sent(
+        &self,
+        group: bool,
+        target: i64,
+        receipt: &MessageReceipt,
+        content: WireChain,
+    ) {
+        self.sent.insert(group, target, &receipt.rands);
+        self.record_message(HistoryEntry {
+            group,
+            target,
+            sender: self.uin,
+            source: Source {
+                seqs: receipt.seqs.clone(),
+                rands: receipt.rands.clone(),
+                time: receipt.time as i32,
+            },
+            content,
+        });
+    }
+
+    /// 等待下一个符合条件的事件的等待者。
+    pub fn waiters(&self) -> &Waiters {
+        &self.waiters
+    }
+
+    /// 添加事件队列，返回用于取消订阅的编号。
+    pub fn subscribe(&self, subscriber: Subscriber) -> u64 {
+        let id = self.next_subscriber.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut subscribers) = self.subscribers.write() {
+            subscribers.push((id, Arc::new(subscriber)));
+        }
+        id
+    }
+
+    /// 当前的事件队列编号，按添加顺序排列。
+    pub fn subscription_ids(&self) -> Vec<u64> {
+        self.subscribers
+            .read()
+            .map(|subscribers| subscribers.iter().map(|(id, _)| *id).collect())
+            .unwrap_or_default()
+    }
+
+    /// 移除事件队列，返回是否存在对应的事件队列。
+    pub fn unsubscribe(&self, id: u64) -> bool {
+        let Ok(mut subscribers) = self.subscribers.write() else {
+            return false;
+        };
+        let count = subscribers.len();
+        subscribers.retain(|(sub_id, _)| *sub_id != id);
+        subscribers.len() != count
     }
-}
 
-#[async_trait]
-impl Handler for PyHandler {
     async fn handle(&self, event: QEvent) {
-        let event_repr = format!("{event:?}");
         if let QEvent::ClientDisconnect(e) = event {
-            match e.inner {
+            let reason = match e.inner {
                 DisconnectReason::Network => {
                     tracing::error!("网络错误, 尝试重连");
+                    "network"
                 }
                 DisconnectReason::Actively(net) => match net {
                     NetworkStatus::Drop => {
                         tracing::error!("意料之外的内存释放");
+                        "dropped"
                     }
                     NetworkStatus::NetworkOffline => {
                         tracing::error!("网络离线, 尝试重连");
+                        "network_offline"
                     }
                     NetworkStatus::KickedOffline => {
                         tracing::error!("其他设备登录, 被踢下线");
+                        "kicked_offline"
                     }
                     NetworkStatus::MsfOffline => {
                         tracing::error!("服务器强制下线");
+                        "msf_offline"
                     }
-                    _ => {}
+                    NetworkStatus::Stop => "stopped",
+                    _ => "unknown",
                 },
-            }
+            };
+            self.dispatch(Event::Disconnected {
+                reason: reason.into(),
+            })
+            .await;
             return;
         }
-        let py_event = match self::converter::convert(event).await {
-            Ok(obj) => obj,
-            Err(e) => {
-                tracing::error!("转换事件失败: {}", event_repr);
-                py_use(|py| e.print_and_set_sys_last_vars(py));
-                return;
-            }
+        let Some(raw) = self::converter::capture(event).await else {
+            return;
         };
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&raw);
+        }
+        let mut event = raw.into_event(self.uin);
+        if let Some(dedup) = &self.dedup && dedup.is_duplicate(&event) {
+            tracing::debug!("忽略重复事件: {:?}", event);
+            return;
+        }
+        self.roles.apply(&mut event);
+        self.roster.observe(&event);
+        match HistoryEntry::from_event(&event) {
+            Some(entry) => self.record_message(entry),
+            None => self.history.fill_recall(&mut event),
+        }
+        self.mark_from_client(&mut event).await;
+        self.dispatch(event).await;
+    }
+
+    /// 标记由当前客户端发送的消息。
+    async fn mark_from_client(&self, event: &mut Event) {
+        let (group, target, source, from_client) = match event {
+            Event::SelfGroupMessage {
+                source,
+                group,
+                from_client,
+                ..
+            } => (true, *group, source, from_client),
+            Event::SelfFriendMessage {
+                source,
+                target,
+                from_client,
+                ..
+            } => (false, *target, source, from_client),
+            _ => return,
+        };
+        let Some(&rand) = source.rands.first() else {
+            return;
+        };
+        *from_client = self.sent.was_sent(group, target, rand).await;
+    }
+
+    /// 将事件交给等待者，未被消费时投递给所有通过过滤的事件队列。
+    pub async fn dispatch(&self, event: Event) {
+        if self.waiters.offer(&event, self.uin) {
+            tracing::debug!("事件已被等待者消费: {:?}", event);
+            return;
+        }
+        let type_name = event.type_name();
+        let subscribers: Vec<Arc<Subscriber>> = self
+            .subscribers
+            .read()
+            .map(|subscribers| subscribers.iter().map(|(_, sub)| sub.clone()).collect())
+            .unwrap_or_default();
+        let (ordered, concurrent): (Vec<&Arc<Subscriber>>, Vec<&Arc<Subscriber>>) = subscribers
+            .iter()
+            .filter(|sub| sub.accepts(&event, self.uin))
+            .partition(|sub| sub.ordered.is_some());
         let mut handles: Vec<tokio::task::JoinHandle<Result<(), PyErr>>> = vec![];
-        Python::with_gil(|py| {
-            if py_event.as_ref(py).is_empty() {
-                return;
-            }
-            let client = match py_client_refs(py).get_item(self.uin) {
-                Ok(client) => client,
-                Err(e) => {
-                    tracing::error!("获取 client 引用失败: {}", event_repr);
-                    e.print_and_set_sys_last_vars(py);
-                    return;
-                }
-            };
-            match py_event.as_ref(py).set_item("client", client) {
-                Ok(_) => {}
-                Err(e) => {
-                    tracing::error!("设置 client 引用失败: {}", event_repr);
-                    e.print_and_set_sys_last_vars(py);
-                    return;
+        let mut py_event: Option<PyObject> = None;
+        if !concurrent.is_empty() {
+            Python::with_gil(|py| {
+                let client = match py_client_refs(py).get_item(self.uin) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        self.dead_letters.report(
+                            Stage::ClientLookup,
+                            type_name,
+                            &format!("{event:?}"),
+                            None,
+                            e,
+                        );
+                        return;
+                    }
+                };
+                let converted = match event.clone().into_py_event(py, client.into_py(py)) {
+                    Ok(converted) => converted,
+                    Err(e) => {
+                        self.dead_letters.report(
+                            Stage::Conversion,
+                            type_name,
+                            &format!("{event:?}"),
+                            None,
+                            e,
+                        );
+                        return;
+                    }
+                };
+                for consumer in concurrent.iter().map(|sub| sub.consumer.clone_ref(py)) {
+                    let locals = self.locals.clone();
+                    let event = converted.clone_ref(py);
+                    handles.push(tokio::spawn(
+                        async move { consumer.put(&locals, event).await },
+                    ));
                 }
-            };
-            let args: Py<PyTuple> = (py_event,).into_py(py);
-            for q in self.queues.as_ref(py).iter().map(|q| q.into_py(py)) {
-                let locals = self.locals.clone();
-                let args = args.clone_ref(py);
-                handles.push(tokio::spawn(async move {
-                    py_try(|py| {
-                        into_future_with_locals(
-                            &locals,
-                            q.as_ref(py).getattr("put")?.call1(args.as_ref(py))?,
-                        )
-                    })?
-                    .await?;
-                    Ok(())
-                }));
+                py_event = Some(converted);
+            });
+        }
+        for sub in ordered {
+            if let Some(dispatch) = &sub.ordered {
+                dispatch
+                    .push(
+                        &sub.consumer,
+                        &self.locals,
+                        self.uin,
+                        &self.dead_letters,
+                        event.clone(),
+                    )
+                    .await;
             }
-        });
+        }
         for handle in handles {
             match handle.await {
                 Err(err) => {
-                    tracing::error!("事件处理失败失败: {}", event_repr);
+                    tracing::error!("事件处理失败失败: {:?}", event);
                     tracing::error!("Rust 无法收集回调结果: {:?}", err);
                 }
                 Ok(Err(err)) => {
-                    tracing::error!("事件处理失败: {}", event_repr);
-                    py_use(|py| err.print_and_set_sys_last_vars(py));
+                    let py_event = py_use(|py| py_event.as_ref().map(|e| e.clone_ref(py)));
+                    self.dead_letters.report(
+                        Stage::Put,
+                        type_name,
+                        &format!("{event:?}"),
+                        py_event,
+                        err,
+                    );
                 }
                 Ok(Ok(())) => {}
             };
         }
     }
 }
+
+pub struct PyHandler(Arc<EventDispatcher>);
+
+impl PyHandler {
+    pub fn new(
+        subscribers: Vec<Subscriber>,
+        locals: TaskLocals,
+        uin: i64,
+        dedup_window: Option<Duration>,
+        recorder: Option<EventRecorder>,
+        archive: Option<MessageArchive>,
+        dead_letter: Option<PyObject>,
+    ) -> Self {
+        Self(Arc::new(EventDispatcher {
+            dead_letters: Arc::new(DeadLetters::new(dead_letter, locals.clone())),
+            next_subscriber: AtomicU64::new(subscribers.len() as u64),
+            subscribers: RwLock::new(
+                subscribers
+                    .into_iter()
+                    .enumerate()
+                    .map(|(id, sub)| (id as u64, Arc::new(sub)))
+                    .collect(),
+            ),
+            locals,
+            uin,
+            dedup: dedup_window.map(EventDeduper::new),
+            recorder,
+            archive,
+            sent: SentMessages::default(),
+            roles: MemberRoles::default(),
+            roster: RosterSnapshot::default(),
+            waiters: Waiters::default(),
+            history: MessageHistory::default(),
+        }))
+    }
+
+    pub fn dispatcher(&self) -> Arc<EventDispatcher> {
+        self.0.clone()
+    }
+}
+
+#[async_trait]
+impl Handler for PyHandler {
+    async fn handle(&self, event: QEvent) {
+        self.0.handle(event).await;
+    }
+}
@@ -0,0 +1,983 @@
+//! 事件的 Rust 侧表示。
+//!
+//! 事件先由 [`capture`](super::converter::capture) 提取原始数据并转换为 [`Event`]，再在持有 GIL 时转换为 [`structs`](super::structs) 中的 Python 事件对象。
+//! [`Event`] 同时也是事件的 JSON 传输格式。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{MessageSender, MessageSource};
+use crate::client::structs::Friend;
+use crate::dict;
+use crate::message::wire::{check_version, ChainEnvelope, WireChain, WIRE_VERSION};
+use crate::utils::{datetime_from_ts, py_permission_from_name, timedelta_from_secs};
+
+/// 消息的元信息。
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Source {
+    pub seqs: Vec<i32>,
+    pub rands: Vec<i32>,
+    pub time: i32,
+}
+
+impl Source {
+    pub fn into_py_source(self, py: Python) -> PyResult<MessageSource> {
+        MessageSource::new(py, &self.seqs, &self.rands, self.time)
+    }
+
+    fn from_py(source: &MessageSource, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            seqs: source.raw_seqs.as_ref(py).extract()?,
+            rands: source.raw_rands.as_ref(py).extract()?,
+            time: timestamp(source.time.as_ref(py))? as i32,
+        })
+    }
+}
+
+/// 消息携带的发送者信息。
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct SenderInfo {
+    pub uin: i64,
+    /// 昵称，群消息不携带
+    pub nickname: Option<String>,
+    /// 群名片，仅群消息携带，未设置时为空字符串
+    pub card_name: Option<String>,
+    /// 群成员权限，为 `Owner`、`Admin` 或 `Member`，未知时为空
+    pub role: Option<String>,
+}
+
+impl SenderInfo {
+    pub fn into_py_sender(self, py: Python) -> PyResult<MessageSender> {
+        Ok(MessageSender {
+            uin: self.uin,
+            nickname: self.nickname,
+            card_name: self.card_name,
+            role: match self.role {
+                Some(role) => py_permission_from_name(py, &role)?.into_py(py),
+                None => py.None(),
+            },
+        })
+    }
+
+    fn from_py(sender: &MessageSender, py: Python) -> PyResult<Self> {
+        let role = sender.role.as_ref(py);
+        Ok(Self {
+            uin: sender.uin,
+            nickname: sender.nickname.clone(),
+            card_name: sender.card_name.clone(),
+            role: if role.is_none() {
+                None
+            } else {
+                Some(role.getattr("name")?.extract()?)
+            },
+        })
+    }
+}
+
+/// 群成员离开群的方式。
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaveKind {
+    /// 主动退群
+    #[default]
+    Quit,
+    /// 被移出群
+    Kicked,
+}
+
+impl LeaveKind {
+    pub fn from_operator(operator_uin: Option<i64>) -> Self {
+        match operator_uin {
+            Some(_) => Self::Kicked,
+            None => Self::Quit,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Kicked => "kicked",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct GroupInfoChange {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(tag = "type_name")]
+pub enum Event {
+    GroupMessage {
+        source: Source,
+        content: WireChain,
+        group: i64,
+        sender: i64,
+        #[serde(default)]
+        group_name: String,
+        #[serde(default)]
+        sender_info: SenderInfo,
+    },
+    GroupRecallMessage {
+        time: i64,
+        group: i64,
+        author: i64,
+        operator: i64,
+        seq: i32,
+        /// 原消息内容，仅在原消息被缓存时可用
+        #[serde(default)]
+        content: Option<WireChain>,
+    },
+    FriendMessage {
+        source: Source,
+        content: WireChain,
+        sender: i64,
+        #[serde(default)]
+        sender_info: SenderInfo,
+    },
+    FriendRecallMessage {
+        time: i64,
+        author: i64,
+        seq: i32,
+        /// 原消息内容，仅在原消息被缓存时可用
+        #[serde(default)]
+        content: Option<WireChain>,
+    },
+    TempMessage {
+        source: Source,
+        content: WireChain,
+        group: i64,
+        sender: i64,
+        #[serde(default)]
+        sender_info: SenderInfo,
+    },
+    /// 本账号发送的群消息
+    SelfGroupMessage {
+        source: Source,
+        content: WireChain,
+        group: i64,
+        sender: i64,
+        #[serde(default)]
+        group_name: String,
+        #[serde(default)]
+        sender_info: SenderInfo,
+        /// 是否由当前客户端发送
+        #[serde(default)]
+        from_client: bool,
+    },
+    /// 本账号发送的好友消息
+    SelfFriendMessage {
+        source: Source,
+        content: WireChain,
+        sender: i64,
+        /// 接收消息的好友
+        target: i64,
+        #[serde(default)]
+        sender_info: SenderInfo,
+        /// 是否由当前客户端发送
+        #[serde(default)]
+        from_client: bool,
+    },
+    GroupNudge {
+        group: i64,
+        sender: i64,
+        receiver: i64,
+    },
+    FriendNudge {
+        sender: i64,
+    },
+    NewFriend {
+        friend: Friend,
+    },
+    NewMember {
+        group: i64,
+        member: i64,
+    },
+    MemberLeaveGroup {
+        group_uin: i64,
+        member_uin: i64,
+        /// 移出成员的操作者，主动退群时为空
+        #[serde(default)]
+        operator_uin: Option<i64>,
+        #[serde(default)]
+        kind: LeaveKind,
+    },
+    BotJoinGroup {
+        group_uin: i64,
+    },
+    BotLeaveGroup {
+        group_uin: i64,
+    },
+    BotKicked {
+        group_uin: i64,
+        operator_uin: i64,
+    },
+    GroupDisband {
+        group_uin: i64,
+        operator_uin: i64,
+    },
+    FriendDeleted {
+        friend_uin: i64,
+    },
+    GroupMute {
+        group: i64,
+        operator: i64,
+        status: bool,
+    },
+    MemberMute {
+        group: i64,
+        operator: i64,
+        target: i64,
+        /// 禁言秒数，为 0 时表示解除禁言
+        duration: u64,
+    },
+    MemberPermissionChange {
+        group: i64,
+        target: i64,
+        permission: u8,
+    },
+    GroupInfoUpdate {
+        group: i64,
+        operator: i64,
+        info: GroupInfoChange,
+    },
+    /// 群名片变更，由列表快照比较得出
+    MemberCardChange {
+        group: i64,
+        member: i64,
+        old: String,
+        new: String,
+    },
+    /// 群专属头衔变更，由列表快照比较得出
+    MemberTitleChange {
+        group: i64,
+        member: i64,
+        old: String,
+        new: String,
+    },
+    /// 群公告变更，由列表快照比较得出
+    GroupMemoChange {
+        group: i64,
+        old: String,
+        new: String,
+    },
+    /// 好友备注变更，由列表快照比较得出
+    FriendRemarkChange {
+        friend_uin: i64,
+        old: String,
+        new: String,
+    },
+    /// 离线期间加入群的成员，由列表快照比较得出
+    OfflineMemberJoin {
+        group: i64,
+        member: i64,
+    },
+    /// 离线期间离开群的成员，由列表快照比较得出
+    OfflineMemberLeave {
+        group: i64,
+        member: i64,
+    },
+    NewFriendRequest {
+        seq: i64,
+        uin: i64,
+        nickname: String,
+        message: String,
+    },
+    JoinGroupRequest {
+        seq: i64,
+        time: i64,
+        group_uin: i64,
+        group_name: String,
+        request_uin: i64,
+        request_nickname: String,
+        suspicious: bool,
+        invitor_uin: Option<i64>,
+        invitor_nickname: Option<String>,
+    },
+    JoinGroupInvitation {
+        seq: i64,
+        time: i64,
+        group_uin: i64,
+        group_name: String,
+        invitor_uin: i64,
+        invitor_nickname: String,
+    },
+    UnknownEvent {
+        internal_repr: String,
+    },
+    Connected,
+    Disconnected {
+        reason: String,
+    },
+    Reconnecting {
+        attempt: u32,
+        /// 距离下次重连的秒数
+        delay: f64,
+    },
+    Reconnected,
+    LoggedOut {
+        /// 重连失败的原因，不是因为重连失败而下线时为 `None`
+        #[serde(default)]
+        error: Option<String>,
+    },
+    KickedOffline {
+        title: String,
+        tips: String,
+    },
+    MSFOffline {
+        title: String,
+        info: String,
+    },
+}
+
+/// 从 Unix 时间戳或 `datetime` 对象获取秒数。
+pub fn timestamp(time: &PyAny) -> PyResult<i64> {
+    match time.extract::<i64>() {
+        Ok(ts) => Ok(ts),
+        Err(_) => Ok(time.call_method0("timestamp")?.extract::<f64>()? as i64),
+    }
+}
+
+impl Event {
+    pub const TYPE_NAMES: &'static [&'static str] = &[
+        "GroupMessage",
+        "GroupRecallMessage",
+        "FriendMessage",
+        "FriendRecallMessage",
+        "TempMessage",
+        "SelfGroupMessage",
+        "SelfFriendMessage",
+        "GroupNudge",
+        "FriendNudge",
+        "NewFriend",
+        "NewMember",
+        "MemberLeaveGroup",
+        "BotJoinGroup",
+        "BotLeaveGroup",
+        "BotKicked",
+        "GroupDisband",
+        "FriendDeleted",
+        "GroupMute",
+        "MemberMute",
+        "MemberPermissionChange",
+        "GroupInfoUpdate",
+        "MemberCardChange",
+        "MemberTitleChange",
+        "GroupMemoChange",
+        "FriendRemarkChange",
+        "OfflineMemberJoin",
+        "OfflineMemberLeave",
+        "NewFriendRequest",
+        "JoinGroupRequest",
+        "JoinGroupInvitation",
+        "UnknownEvent",
+        "Connected",
+        "Disconnected",
+        "Reconnecting",
+        "Reconnected",
+        "LoggedOut",
+        "KickedOffline",
+        "MSFOffline",
+    ];
+
+    /// 事件所在的群。
+    pub fn group(&self) -> Option<i64> {
+        match self {
+            Event::GroupMessage { group, .. }
+            | Event::GroupRecallMessage { group, .. }
+            | Event::TempMessage { group, .. }
+            | Event::SelfGroupMessage { group, .. }
+            | Event::GroupNudge { group, .. }
+            | Event::NewMember { group, .. }
+            | Event::GroupMute { group, .. }
+            | Event::MemberMute { group, .. }
+            | Event::MemberPermissionChange { group, .. }
+            | Event::GroupInfoUpdate { group, .. }
+            | Event::MemberCardChange { group, .. }
+            | Event::MemberTitleChange { group, .. }
+            | Event::GroupMemoChange { group, .. }
+            | Event::OfflineMemberJoin { group, .. }
+            | Event::OfflineMemberLeave { group, .. } => Some(*group),
+            Event::MemberLeaveGroup { group_uin, .. }
+            | Event::BotJoinGroup { group_uin }
+            | Event::BotLeaveGroup { group_uin }
+            | Event::BotKicked { group_uin, .. }
+            | Event::GroupDisband { group_uin, .. }
+            | Event::JoinGroupRequest { group_uin, .. }
+            | Event::JoinGroupInvitation { group_uin, .. } => Some(*group_uin),
+            _ => None,
+        }
+    }
+
+    /// 触发事件的用户。
+    pub fn user(&self) -> Option<i64> {
+        match self {
+            Event::GroupMessage { sender, .. }
+            | Event::FriendMessage { sender, .. }
+            | Event::TempMessage { sender, .. }
+            | Event::SelfGroupMessage { sender, .. }
+            | Event::SelfFriendMessage { sender, .. }
+            | Event::GroupNudge { sender, .. }
+            | Event::FriendNudge { sender } => Some(*sender),
+            Event::GroupRecallMessage { operator, .. }
+            | Event::GroupMute { operator, .. }
+            | Event::MemberMute { operator, .. }
+            | Event::GroupInfoUpdate { operator, .. } => Some(*operator),
+            Event::FriendRecallMessage { author, .. } => Some(*author),
+            Event::NewFriend { friend } => Some(friend.uin),
+            Event::NewMember { member, .. }
+            | Event::MemberCardChange { member, .. }
+            | Event::MemberTitleChange { member, .. }
+            | Event::OfflineMemberJoin { member, .. }
+            | Event::OfflineMemberLeave { member, .. } => Some(*member),
+            Event::MemberLeaveGroup { member_uin, .. } => Some(*member_uin),
+            Event::GroupDisband { operator_uin, .. } | Event::BotKicked { operator_uin, .. } => {
+                Some(*operator_uin)
+            }
+            Event::FriendDeleted { friend_uin } | Event::FriendRemarkChange { friend_uin, .. } => {
+                Some(*friend_uin)
+            }
+            Event::MemberPermissionChange { target, .. } => Some(*target),
+            Event::NewFriendRequest { uin, .. } => Some(*uin),
+            Event::JoinGroupRequest { request_uin, .. } => Some(*request_uin),
+            Event::JoinGroupInvitation { invitor_uin, .. } => Some(*invitor_uin),
+            Event::UnknownEvent { .. }
+            | Event::GroupMemoChange { .. }
+            | Event::BotJoinGroup { .. }
+            | Event::BotLeaveGroup { .. }
+            | Event::Connected
+            | Event::Disconnected { .. }
+            | Event::Reconnecting { .. }
+            | Event::Reconnected
+            | Event::LoggedOut { .. }
+            | Event::KickedOffline { .. }
+            | Event::MSFOffline { .. } => None,
+        }
+    }
+
+    /// 消息事件的内容。
+    pub fn content(&self) -> Option<&WireChain> {
+        match self {
+            Event::GroupMessage { content, .. }
+            | Event::FriendMessage { content, .. }
+            | Event::TempMessage { content, .. }
+            | Event::SelfGroupMessage { content, .. }
+            | Event::SelfFriendMessage { content, .. } => Some(content),
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Event::GroupMessage { .. } => "GroupMessage",
+            Event::GroupRecallMessage { .. } => "GroupRecallMessage",
+            Event::FriendMessage { .. } => "FriendMessage",
+            Event::FriendRecallMessage { .. } => "FriendRecallMessage",
+            Event::TempMessage { .. } => "TempMessage",
+            Event::SelfGroupMessage { .. } => "SelfGroupMessage",
+            Event::SelfFriendMessage { .. } => "SelfFriendMessage",
+            Event::GroupNudge { .. } => "GroupNudge",
+            Event::FriendNudge { .. } => "FriendNudge",
+            Event::NewFriend { .. } => "NewFriend",
+            Event::NewMember { .. } => "NewMember",
+            Event::MemberLeaveGroup { .. } => "MemberLeaveGroup",
+            Event::BotJoinGroup { .. } => "BotJoinGroup",
+            Event::BotLeaveGroup { .. } => "BotLeaveGroup",
+            Event::BotKicked { .. } => "BotKicked",
+            Event::GroupDisband { .. } => "GroupDisband",
+            Event::FriendDeleted { .. } => "FriendDeleted",
+            Event::GroupMute { .. } => "GroupMute",
+            Event::MemberMute { .. } => "MemberMute",
+            Event::MemberPermissionChange { .. } => "MemberPermissionChange",
+            Event::GroupInfoUpdate { .. } => "GroupInfoUpdate",
+            Event::MemberCardChange { .. } => "MemberCardChange",
+            Event::MemberTitleChange { .. } => "MemberTitleChange",
+            Event::GroupMemoChange { .. } => "GroupMemoChange",
+            Event::FriendRemarkChange { .. } => "FriendRemarkChange",
+            Event::OfflineMemberJoin { .. } => "OfflineMemberJoin",
+            Event::OfflineMemberLeave { .. } => "OfflineMemberLeave",
+            Event::NewFriendRequest { .. } => "NewFriendRequest",
+            Event::JoinGroupRequest { .. } => "JoinGroupRequest",
+            Event::JoinGroupInvitation { .. } => "JoinGroupInvitation",
+            Event::UnknownEvent { .. } => "UnknownEvent",
+            Event::Connected => "Connected",
+            Event::Disconnected { .. } => "Disconnected",
+            Event::Reconnecting { .. } => "Reconnecting",
+            Event::Reconnected => "Reconnected",
+            Event::LoggedOut { .. } => "LoggedOut",
+            Event::KickedOffline { .. } => "KickedOffline",
+            Event::MSFOffline { .. } => "MSFOffline",
+        }
+    }
+
+    /// 转换为投递给事件队列的 Python 事件对象。
+    pub fn into_py_event(self, py: Python, client: PyObject) -> PyResult<PyObject> {
+        use super::structs as py_ev;
+
+        Ok(match self {
+            Event::GroupMessage {
+                source,
+                content,
+                group,
+                sender,
+                group_name,
+                sender_info,
+            } => py_ev::GroupMessage {
+                client,
+                source: source.into_py_source(py)?,
+                content: content.into_py_chain(py)?,
+                group,
+                sender,
+                group_name,
+                sender_info: sender_info.into_py_sender(py)?,
+            }
+            .into_py(py),
+            Event::GroupRecallMessage {
+                time,
+                group,
+                author,
+                operator,
+                seq,
+                content,
+            } => py_ev::GroupRecallMessage {
+                client,
+                time: datetime_from_ts(py, time)?.into_py(py),
+                group,
+                author,
+                operator,
+                seq,
+                content: content
+                    .map(|content| content.into_py_chain(py))
+                    .transpose()?
+                    .into_py(py),
+            }
+            .into_py(py),
+            Event::FriendMessage {
+                source,
+                content,
+                sender,
+                sender_info,
+            } => py_ev::FriendMessage {
+                client,
+                source: source.into_py_source(py)?,
+                content: content.into_py_chain(py)?,
+                sender,
+                sender_info: sender_info.into_py_sender(py)?,
+            }
+            .into_py(py),
+            Event::FriendRecallMessage {
+                time,
+                author,
+                seq,
+                content,
+            } => py_ev::FriendRecallMessage {
+                client,
+                time: datetime_from_ts(py, time)?.into_py(py),
+                author,
+                seq,
+                content: content
+                    .map(|content| content.into_py_chain(py))
+                    .transpose()?
+                    .into_py(py),
+            }
+            .into_py(py),
+            Event::TempMessage {
+                source,
+                content,
+                group,
+                sender,
+                sender_info,
+            } => py_ev::TempMessage {
+                client,
+                source: source.into_py_source(py)?,
+                content: content.into_py_chain(py)?,
+                group,
+                sender,
+                sender_info: sender_info.into_py_sender(py)?,
+            }
+            .into_py(py),
+            Event::SelfGroupMessage {
+                source,
+                content,
+                group,
+                sender,
+                group_name,
+                sender_info,
+                from_client,
+            } => py_ev::SelfGroupMessage {
+                client,
+                source: source.into_py_source(py)?,
+                content: content.into_py_chain(py)?,
+                group,
+                sender,
+                group_name,
+                sender_info: sender_info.into_py_sender(py)?,
+                from_client,
+            }
+            .into_py(py),
+            Event::SelfFriendMessage {
+                source,
+                content,
+                sender,
+                target,
+                sender_info,
+                from_client,
+            } => py_ev::SelfFriendMessage {
+                client,
+                source: source.into_py_source(py)?,
+                content: content.into_py_chain(py)?,
+                sender,
+                target,
+                sender_info: sender_info.into_py_sender(py)?,
+                from_client,
+            }
+            .into_py(py),
+            Event::GroupNudge {
+                group,
+                sender,
+                receiver,
+            } => py_ev::GroupNudge {
+                client,
+                group,
+                sender,
+                receiver,
+            }
+            .into_py(py),
+            Event::FriendNudge { sender } => py_ev::FriendNudge { client, sender }.into_py(py),
+            Event::NewFriend { friend } => py_ev::NewFriend { client, friend }.into_py(py),
+            Event::NewMember { group, member } => py_ev::NewMember {
+                client,
+                group,
+                member,
+            }
+            .into_py(py),
+            Event::MemberLeaveGroup {
+                group_uin,
+                member_uin,
+                operator_uin,
+                kind,
+            } => py_ev::MemberLeaveGroup {
+                client,
+                group_uin,
+                member_uin,
+                operator_uin,
+                kind: kind.as_str().into(),
+            }
+            .into_py(py),
+            Event::BotJoinGroup { group_uin } => {
+                py_ev::BotJoinGroup { client, group_uin }.into_py(py)
+            }
+            Event::BotLeaveGroup { group_uin } => {
+                py_ev::BotLeaveGroup { client, group_uin }.into_py(py)
+            }
+            Event::BotKicked {
+                group_uin,
+                operator_uin,
+            } => py_ev::BotKicked {
+                client,
+                group_uin,
+                operator_uin,
+            }
+            .into_py(py),
+            Event::GroupDisband {
+                group_uin,
+                operator_uin,
+            } => py_ev::GroupDisband {
+                client,
+                group_uin,
+                operator_uin,
+            }
+            .into_py(py),
+            Event::FriendDeleted { friend_uin } => {
+                py_ev::FriendDeleted { client, friend_uin }.into_py(py)
+            }
+            Event::GroupMute {
+                group,
+                operator,
+                status,
+            } => py_ev::GroupMute {
+                client,
+                group,
+                operator,
+                status,
+            }
+            .into_py(py),
+            Event::MemberMute {
+                group,
+                operator,
+                target,
+                duration,
+            } => py_ev::MemberMute {
+                client,
+                group,
+                operator,
+                target,
+                duration: if duration != 0 {
+                    timedelta_from_secs(py, duration)?.into_py(py)
+                } else {
+                    false.into_py(py)
+                },
+            }
+            .into_py(py),
+            Event::MemberPermissionChange {
+                group,
+                target,
+                permission,
+            } => py_ev::MemberPermissionChange {
+                client,
+                group,
+                target,
+                permission,
+            }
+            .into_py(py),
+            Event::GroupInfoUpdate {
+                group,
+                operator,
+                info,
+            } => py_ev::GroupInfoUpdate {
+                client,
+                group,
+                operator,
+                info: dict! {py, name: info.name}.into_py(py),
+            }
+            .into_py(py),
+            Event::MemberCardChange {
+                group,
+                member,
+                old,
+                new,
+            } => py_ev::MemberCardChange {
+                client,
+                group,
+                member,
+                old,
+                new,
+            }
+            .into_py(py),
+            Event::MemberTitleChange {
+                group,
+                member,
+                old,
+                new,
+            } => py_ev::MemberTitleChange {
+                client,
+                group,
+                member,
+                old,
+                new,
+            }
+            .into_py(py),
+            Event::GroupMemoChange { group, old, new } => py_ev::GroupMemoChange {
+                client,
+                group,
+                old,
+                new,
+            }
+            .into_py(py),
+            Event::FriendRemarkChange {
+                friend_uin,
+                old,
+                new,
+            } => py_ev::FriendRemarkChange {
+                client,
+                friend_uin,
+                old,
+                new,
+            }
+            .into_py(py),
+            Event::OfflineMemberJoin { group, member } => py_ev::OfflineMemberJoin {
+                client,
+                group,
+                member,
+            }
+            .into_py(py),
+            Event::OfflineMemberLeave { group, member } => py_ev::OfflineMemberLeave {
+                client,
+                group,
+                member,
+            }
+            .into_py(py),
+            Event::NewFriendRequest {
+                seq,
+                uin,
+                nickname,
+                message,
+            } => py_ev::NewFriendRequest {
+                client,
+                seq,
+                uin,
+                nickname,
+                message,
+            }
+            .into_py(py),
+            Event::JoinGroupRequest {
+                seq,
+                time,
+                group_uin,
+                group_name,
+                request_uin,
+                request_nickname,
+                suspicious,
+                invitor_uin,
+                invitor_nickname,
+            } => py_ev::JoinGroupRequest {
+                client,
+                seq,
+                time: datetime_from_ts(py, time)?.into_py(py),
+                group_uin,
+                group_name,
+                request_uin,
+                request_nickname,
+                suspicious,
+                invitor_uin,
+                invitor_nickname,
+            }
+            .into_py(py),
+            Event::JoinGroupInvitation {
+                seq,
+                time,
+                group_uin,
+                group_name,
+                invitor_uin,
+                invitor_nickname,
+            } => py_ev::JoinGroupInvitation {
+                client,
+                seq,
+                time: datetime_from_ts(py, time)?.into_py(py),
+                group_uin,
+                group_name,
+                invitor_uin,
+                invitor_nickname,
+            }
+            .into_py(py),
+            Event::UnknownEvent { internal_repr } => {
+                py_ev::UnknownEvent {
+                    client,
+                    internal_repr,
+                }
+                .into_py(py)
+            }
+            Event::Connected => py_ev::Connected { client }.into_py(py),
+            Event::Disconnected { reason } => py_ev::Disconnected { client, reason }.into_py(py),
+            Event::Reconnecting { attempt, delay } => py_ev::Reconnecting {
+                client,
+                attempt,
+                delay: timedelta_from_secs(py, delay)?.into_py(py),
+            }
+            .into_py(py),
+            Event::Reconnected => py_ev::Reconnected { client }.into_py(py),
+            Event::LoggedOut { error } => py_ev::LoggedOut { client, error }.into_py(py),
+            Event::KickedOffline { title, tips } => {
+                py_ev::KickedOffline {
+                    client,
+                    title,
+                    tips,
+                }
+                .into_py(py)
+            }
+            Event::MSFOffline { title, info } => py_ev::MSFOffline {
+                client,
+                title,
+                info,
+            }
+            .into_py(py),
+        })
+    }
+
+    /// 从事件对象或事件字典转换，`content` 字段需为元素字典列表。
+    pub fn from_py(py: Python, event: &PyAny) -> PyResult<Self> {
+        let data = match event.downcast::<PyDict>() {
+            Ok(data) => data,
+            Err(_) => {
+                let data = dict! {py, type_name: event.getattr("type_name")?};
+                for name in event.getattr("__match_args__")?.iter()? {
+                    let name = name?;
+                    data.set_item(name, event.getattr(name.downcast::<PyString>()?)?)?;
+                }
+                data
+            }
+        };
+        let mut map = serde_json::Map::new();
+        for (key, value) in data.iter() {
+            let key: String = key.extract()?;
+            let value: Value = match key.as_str() {
+                "client" => continue,
+                "content" if value.is_none() => Value::Null,
+                "content" => to_value(WireChain::from_py_list(value)?)?,
+                "source" => to_value(Source::from_py(&value.extract::<MessageSource>()?, py)?)?,
+                "sender_info" => match value.extract::<MessageSender>() {
+                    Ok(sender) => to_value(SenderInfo::from_py(&sender, py)?)?,
+                    Err(_) => pythonize::depythonize(value)
+                        .map_err(|e| PyValueError::new_err(format!("无法序列化字段 {key}: {e}")))?,
+                },
+                "friend" => to_value(value.extract::<Friend>()?)?,
+                "time" => timestamp(value)?.into(),
+                "duration" if value.is_instance_of::<PyBool>()? => 0.into(),
+                "duration" => (value.call_method0("total_seconds")?.extract::<f64>()? as u64).into(),
+                "delay" => value.call_method0("total_seconds")?.extract::<f64>()?.into(),
+                _ => pythonize::depythonize(value)
+                    .map_err(|e| PyValueError::new_err(format!("无法序列化字段 {key}: {e}")))?,
+            };
+            map.insert(key, value);
+        }
+        serde_json::from_value(Value::Object(map))
+            .map_err(|e| PyValueError::new_err(format!("无法序列化事件: {e}")))
+    }
+}
+
+fn to_value(value: impl Serialize) -> PyResult<Value> {
+    serde_json::to_value(value).map_err(|e| PyValueError::new_err(format!("无法序列化事件: {e}")))
+}
+
+/// 带版本号的事件。
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct EventEnvelope {
+    pub version: u32,
+    pub event: Event,
+}
+
+impl EventEnvelope {
+    pub fn new(event: Event) -> Self {
+        Self {
+            version: WIRE_VERSION,
+            event,
+        }
+    }
+}
+
+#[pyfunction]
+pub fn event_to_json(py: Python, event: &PyAny) -> PyResult<String> {
+    serde_json::to_string(&EventEnvelope::new(Event::from_py(py, event)?))
+        .map_err(|e| PyValueError::new_err(format!("无法序列化事件: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, client=None))]
+pub fn event_from_json(py: Python, data: &str, client: Option<PyObject>) -> PyResult<PyObject> {
+    let envelope: EventEnvelope = serde_json::from_str(data)
+        .map_err(|e| PyValueError::new_err(format!("无法解析事件: {e}")))?;
+    check_version(envelope.version)?;
+    envelope
+        .event
+        .into_py_event(py, client.unwrap_or_else(|| py.None()))
+}
+
+#[pyfunction]
+pub fn wire_schema(kind: &str) -> PyResult<String> {
+    let schema = match kind {
+        "chain" => schemars::schema_for!(ChainEnvelope),
+        "event" => schemars::schema_for!(EventEnvelope),
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "未知的传输格式类型: {other}"
+            )))
+        }
+    };
+    serde_json::to_string_pretty(&schema)
+        .map_err(|e| PyValueError::new_err(format!("无法生成 JSON Schema: {e}")))
+}
@@ -0,0 +1,573 @@
+//! 事件的原始协议数据。
+//!
+//! [`RawEvent`] 保存 RICQ 事件中与客户端无关的部分，可以序列化后写入记录文件，
+//! 并在没有网络连接的情况下重新转换为 [`Event`]。
+
+use base64::Engine as _;
+use prost::Message as _;
+use ricq::msg::MessageChain;
+use ricq_core::pb::msg as pb;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::model::{Event, GroupInfoChange, LeaveKind, SenderInfo, Source};
+use crate::client::structs::Friend;
+use crate::message::wire::{WireChain, WireElement};
+
+/// 记录文件的版本，结构发生不兼容的变化时递增。
+pub const RECORD_VERSION: u32 = 1;
+
+fn encode_b64(data: Vec<u8>) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn decode_b64<E: serde::de::Error>(data: &str) -> Result<Vec<u8>, E> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(E::custom)
+}
+
+/// 以 base64 编码的 protobuf 元素列表保存的消息链。
+#[derive(Debug, Clone)]
+pub struct RawChain(pub MessageChain);
+
+impl Serialize for RawChain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let elems: Vec<pb::Elem> = self.0.clone().into();
+        serializer.collect_seq(elems.iter().map(|e| encode_b64(e.encode_to_vec())))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawChain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems: Vec<pb::Elem> = Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|data| {
+                pb::Elem::decode(decode_b64::<D::Error>(data)?.as_slice())
+                    .map_err(serde::de::Error::custom)
+            })
+            .try_collect()?;
+        Ok(Self(MessageChain::from(elems)))
+    }
+}
+
+/// 以 base64 编码的 protobuf 数据保存的语音。
+#[derive(Debug, Clone)]
+pub struct RawPtt(pub pb::Ptt);
+
+impl Serialize for RawPtt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode_b64(self.0.encode_to_vec()))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawPtt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = decode_b64::<D::Error>(&String::deserialize(deserializer)?)?;
+        pb::Ptt::decode(data.as_slice())
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// RICQ 事件的原始数据，字段名与 RICQ 保持一致。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum RawEvent {
+    GroupMessage {
+        seqs: Vec<i32>,
+        rands: Vec<i32>,
+        time: i32,
+        group_code: i64,
+        #[serde(default)]
+        group_name: String,
+        #[serde(default)]
+        group_card: String,
+        from_uin: i64,
+        elements: RawChain,
+    },
+    GroupAudioMessage {
+        seqs: Vec<i32>,
+        rands: Vec<i32>,
+        time: i32,
+        group_code: i64,
+        #[serde(default)]
+        group_name: String,
+        #[serde(default)]
+        group_card: String,
+        from_uin: i64,
+        audio: RawPtt,
+    },
+    FriendMessage {
+        seqs: Vec<i32>,
+        rands: Vec<i32>,
+        time: i32,
+        from_uin: i64,
+        #[serde(default)]
+        from_nick: String,
+        #[serde(default)]
+        target: i64,
+        elements: RawChain,
+    },
+    FriendAudioMessage {
+        seqs: Vec<i32>,
+        rands: Vec<i32>,
+        time: i32,
+        from_uin: i64,
+        #[serde(default)]
+        from_nick: String,
+        #[serde(default)]
+        target: i64,
+        audio: RawPtt,
+    },
+    GroupTempMessage {
+        seqs: Vec<i32>,
+        rands: Vec<i32>,
+        time: i32,
+        group_code: i64,
+        from_uin: i64,
+        #[serde(default)]
+        from_nick: String,
+        elements: RawChain,
+    },
+    GroupMessageRecall {
+        time: i64,
+        group_code: i64,
+        author_uin: i64,
+        operator_uin: i64,
+        msg_seq: i32,
+    },
+    FriendMessageRecall {
+        time: i64,
+        friend_uin: i64,
+        msg_seq: i32,
+    },
+    GroupPoke {
+        group_code: i64,
+        sender: i64,
+        receiver: i64,
+    },
+    FriendPoke {
+        sender: i64,
+    },
+    NewFriend {
+        friend: Friend,
+    },
+    NewMember {
+        group_code: i64,
+        member_uin: i64,
+    },
+    GroupLeave {
+        group_code: i64,
+        member_uin: i64,
+        #[serde(default)]
+        operator_uin: Option<i64>,
+    },
+    GroupDisband {
+        group_code: i64,
+        operator_uin: i64,
+    },
+    DeleteFriend {
+        uin: i64,
+    },
+    GroupMute {
+        group_code: i64,
+        operator_uin: i64,
+        target_uin: i64,
+        duration: u64,
+    },
+    MemberPermissionChange {
+        group_code: i64,
+        member_uin: i64,
+        new_permission: u8,
+    },
+    GroupNameUpdate {
+        group_code: i64,
+        operator_uin: i64,
+        group_name: String,
+    },
+    GroupRequest {
+        msg_seq: i64,
+        msg_time: i64,
+        group_code: i64,
+        group_name: String,
+        req_uin: i64,
+        req_nick: String,
+        suspicious: bool,
+        invitor_uin: Option<i64>,
+        invitor_nick: Option<String>,
+    },
+    SelfInvited {
+        msg_seq: i64,
+        msg_time: i64,
+        group_code: i64,
+        group_name: String,
+        invitor_uin: i64,
+        invitor_nick: String,
+    },
+    NewFriendRequest {
+        msg_seq: i64,
+        req_uin: i64,
+        req_nick: String,
+        message: String,
+    },
+    KickedOffline {
+        title: String,
+        tips: String,
+    },
+    MSFOffline {
+        title: String,
+        info: String,
+    },
+    Unknown {
+        repr: String,
+    },
+}
+
+/// 记录文件中的一行。
+#[derive(Serialize, Deserialize)]
+pub struct RecordLine {
+    pub version: u32,
+    /// 接收事件的账号
+    pub uin: i64,
+    /// 接收事件的时间戳
+    pub received: i64,
+    pub event: RawEvent,
+}
+
+/// 按发送者区分群消息与本账号发送的群消息。
+fn group_message(
+    source: Source,
+    content: WireChain,
+    group: i64,
+    group_name: String,
+    sender_info: SenderInfo,
+    self_uin: i64,
+) -> Event {
+    let sender = sender_info.uin;
+    if sender == self_uin {
+        Event::SelfGroupMessage {
+            source,
+            content,
+            group,
+            sender,
+            group_name,
+            sender_info,
+            from_client: false,
+        }
+    } else {
+        Event::GroupMessage {
+            source,
+            content,
+            group,
+            sender,
+            group_name,
+            sender_info,
+        }
+    }
+}
+
+/// 按发送者区分好友消息与本账号发送的好友消息。
+fn friend_message(
+    source: Source,
+    content: WireChain,
+    sender_info: SenderInfo,
+    target: i64,
+    self_uin: i64,
+) -> Event {
+    let sender = sender_info.uin;
+    if sender == self_uin {
+        Event::SelfFriendMessage {
+            source,
+            content,
+            sender,
+            target,
+            sender_info,
+            from_client: false,
+        }
+    } else {
+        Event::FriendMessage {
+            source,
+            content,
+            sender,
+            sender_info,
+        }
+    }
+}
+
+fn group_sender(uin: i64, card_name: String) -> SenderInfo {
+    SenderInfo {
+        uin,
+        nickname: None,
+        card_name: Some(card_name),
+        role: None,
+    }
+}
+
+fn friend_sender(uin: i64, nickname: String) -> SenderInfo {
+    SenderInfo {
+        uin,
+        nickname: Some(nickname),
+        card_name: None,
+        role: None,
+    }
+}
+
+impl RawEvent {
+    /// 转换为事件，`self_uin` 为接收事件的账号，用于识别本账号发送的消息。
+    ///
+    /// 本账号发送的消息的 `from_client` 字段总是为 `false`，由事件分发时按发送记录设置。
+    pub fn into_event(self, self_uin: i64) -> Event {
+        let source = |seqs, rands, time| Source { seqs, rands, time };
+        match self {
+            RawEvent::GroupMessage {
+                seqs,
+                rands,
+                time,
+                group_code,
+                group_name,
+                group_card,
+                from_uin,
+                elements,
+            } => group_message(
+                source(seqs, rands, time),
+                elements.0.into(),
+                group_code,
+                group_name,
+                group_sender(from_uin, group_card),
+                self_uin,
+            ),
+            RawEvent::GroupAudioMessage {
+                seqs,
+                rands,
+                time,
+                group_code,
+                group_name,
+                group_card,
+                from_uin,
+                audio,
+            } => group_message(
+                source(seqs, rands, time),
+                WireChain(vec![WireElement::audio(String::new(), &audio.0)]),
+                group_code,
+                group_name,
+                group_sender(from_uin, group_card),
+                self_uin,
+            ),
+            RawEvent::FriendMessage {
+                seqs,
+                rands,
+                time,
+                from_uin,
+                from_nick,
+                target,
+                elements,
+            } => friend_message(
+                source(seqs, rands, time),
+                elements.0.into(),
+                friend_sender(from_uin, from_nick),
+                target,
+                self_uin,
+            ),
+            RawEvent::FriendAudioMessage {
+                seqs,
+                rands,
+                time,
+                from_uin,
+                from_nick,
+                target,
+                audio,
+            } => friend_message(
+                source(seqs, rands, time),
+                WireChain(vec![WireElement::audio(String::new(), &audio.0)]),
+                friend_sender(from_uin, from_nick),
+                target,
+                self_uin,
+            ),
+            RawEvent::GroupTempMessage {
+                seqs,
+                rands,
+                time,
+                group_code,
+                from_uin,
+                from_nick,
+                elements,
+            } => Event::TempMessage {
+                source: source(seqs, rands, time),
+                content: elements.0.into(),
+                group: group_code,
+                sender: from_uin,
+                sender_info: friend_sender(from_uin, from_nick),
+            },
+            RawEvent::GroupMessageRecall {
+                time,
+                group_code,
+                author_uin,
+                operator_uin,
+                msg_seq,
+            } => Event::GroupRecallMessage {
+                time,
+                group: group_code,
+                author: author_uin,
+                operator: operator_uin,
+                seq: msg_seq,
+                content: None,
+            },
+            RawEvent::FriendMessageRecall {
+                time,
+                friend_uin,
+                msg_seq,
+            } => Event::FriendRecallMessage {
+                time,
+                author: friend_uin,
+                seq: msg_seq,
+                content: None,
+            },
+            RawEvent::GroupPoke {
+                group_code,
+                sender,
+                receiver,
+            } => Event::GroupNudge {
+                group: group_code,
+                sender,
+                receiver,
+            },
+            RawEvent::FriendPoke { sender } => Event::FriendNudge { sender },
+            RawEvent::NewFriend { friend } => Event::NewFriend { friend },
+            RawEvent::NewMember {
+                group_code,
+                member_uin,
+            } if member_uin == self_uin => Event::BotJoinGroup {
+                group_uin: group_code,
+            },
+            RawEvent::NewMember {
+                group_code,
+                member_uin,
+            } => Event::NewMember {
+                group: group_code,
+                member: member_uin,
+            },
+            RawEvent::GroupLeave {
+                group_code,
+                member_uin,
+                operator_uin,
+            } => {
+                // 成员主动退群时没有操作者，部分情况下操作者为成员自身
+                let operator_uin = operator_uin.filter(|&op| op != member_uin);
+                match (member_uin == self_uin, operator_uin) {
+                    (true, Some(operator_uin)) => Event::BotKicked {
+                        group_uin: group_code,
+                        operator_uin,
+                    },
+                    (true, None) => Event::BotLeaveGroup {
+                        group_uin: group_code,
+                    },
+                    (false, operator_uin) => Event::MemberLeaveGroup {
+                        group_uin: group_code,
+                        member_uin,
+                        operator_uin,
+                        kind: LeaveKind::from_operator(operator_uin),
+                    },
+                }
+            }
+            RawEvent::GroupDisband {
+                group_code,
+                operator_uin,
+            } => Event::GroupDisband {
+                group_uin: group_code,
+                operator_uin,
+            },
+            RawEvent::DeleteFriend { uin } => Event::FriendDeleted { friend_uin: uin },
+            RawEvent::GroupMute {
+                group_code,
+                operator_uin,
+                target_uin: 0,
+                duration,
+            } => Event::GroupMute {
+                group: group_code,
+                operator: operator_uin,
+                status: duration == 0,
+            },
+            RawEvent::GroupMute {
+                group_code,
+                operator_uin,
+                target_uin,
+                duration,
+            } => Event::MemberMute {
+                group: group_code,
+                operator: operator_uin,
+                target: target_uin,
+                duration,
+            },
+            RawEvent::MemberPermissionChange {
+                group_code,
+                member_uin,
+                new_permission,
+            } => Event::MemberPermissionChange {
+                group: group_code,
+                target: member_uin,
+                permission: new_permission,
+            },
+            RawEvent::GroupNameUpdate {
+                group_code,
+                operator_uin,
+                group_name,
+            } => Event::GroupInfoUpdate {
+                group: group_code,
+                operator: operator_uin,
+                info: GroupInfoChange { name: group_name },
+            },
+            RawEvent::GroupRequest {
+                msg_seq,
+                msg_time,
+                group_code,
+                group_name,
+                req_uin,
+                req_nick,
+                suspicious,
+                invitor_uin,
+                invitor_nick,
+            } => Event::JoinGroupRequest {
+                seq: msg_seq,
+                time: msg_time,
+                group_uin: group_code,
+                group_name,
+                request_uin: req_uin,
+                request_nickname: req_nick,
+                suspicious,
+                invitor_uin,
+                invitor_nickname: invitor_nick,
+            },
+            RawEvent::SelfInvited {
+                msg_seq,
+                msg_time,
+                group_code,
+                group_name,
+                invitor_uin,
+                invitor_nick,
+            } => Event::JoinGroupInvitation {
+                seq: msg_seq,
+                time: msg_time,
+                group_uin: group_code,
+                group_name,
+                invitor_uin,
+                invitor_nickname: invitor_nick,
+            },
+            RawEvent::NewFriendRequest {
+                msg_seq,
+                req_uin,
+                req_nick,
+                message,
+            } => Event::NewFriendRequest {
+                seq: msg_seq,
+                uin: req_uin,
+                nickname: req_nick,
+                message,
+            },
+            RawEvent::KickedOffline { title, tips } => Event::KickedOffline { title, tips },
+            RawEvent::MSFOffline { title, info } => Event::MSFOffline { title, info },
+            RawEvent::Unknown { repr } => Event::UnknownEvent {
+                internal_repr: repr,
+            },
+        }
+    }
+}
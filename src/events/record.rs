@@ -0,0 +1,120 @@
+//! 事件的记录与回放。
+//!
+//! 记录文件为 JSON Lines 格式，每行为一个 [`RecordLine`]。
+//! 回放时事件会经过与在线时相同的转换与过滤流程，但不需要网络连接与登录。
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::future::try_join_all;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::*;
+use pyo3_asyncio::TaskLocals;
+
+use super::raw::{RawEvent, RecordLine, RECORD_VERSION};
+use super::Subscriber;
+use crate::utils::{py_future, py_try, py_use};
+
+/// 将接收到的事件追加写入记录文件。
+pub struct EventRecorder {
+    uin: i64,
+    file: Mutex<LineWriter<File>>,
+}
+
+impl EventRecorder {
+    pub fn open(uin: i64, path: PathBuf) -> PyResult<Self> {
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| PyIOError::new_err(format!("无法打开事件记录文件 {}: {e}", path.display())))?;
+        Ok(Self {
+            uin,
+            file: Mutex::new(LineWriter::new(file)),
+        })
+    }
+
+    pub fn record(&self, event: &RawEvent) {
+        let line = RecordLine {
+            version: RECORD_VERSION,
+            uin: self.uin,
+            received: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64),
+            event: event.clone(),
+        };
+        let res: Result<(), Box<dyn std::error::Error>> = try {
+            let line = serde_json::to_string(&line)?;
+            let mut file = self.file.lock().map_err(|e| e.to_string())?;
+            writeln!(file, "{line}")?;
+        };
+        if let Err(e) = res {
+            tracing::error!("写入事件记录失败: {}", e);
+        }
+    }
+}
+
+fn read_records(path: &PathBuf) -> PyResult<Vec<RecordLine>> {
+    let file = File::open(path)
+        .map_err(|e| PyIOError::new_err(format!("无法打开事件记录文件 {}: {e}", path.display())))?;
+    let mut records = vec![];
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| PyIOError::new_err(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: RecordLine = serde_json::from_str(&line).map_err(|e| {
+            PyValueError::new_err(format!("无法解析第 {} 行的事件记录: {e}", index + 1))
+        })?;
+        if record.version > RECORD_VERSION {
+            return Err(PyValueError::new_err(format!(
+                "不支持的事件记录版本: {}，当前版本为 {RECORD_VERSION}",
+                record.version
+            )));
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// 按记录顺序将事件逐个投递给事件队列，返回投递的事件数。
+///
+/// 每个事件会等待所有队列的 `put` 完成后再投递下一个。
+#[pyfunction]
+#[pyo3(signature = (path, queues, client = None))]
+pub fn replay_events<'py>(
+    py: Python<'py>,
+    path: PathBuf,
+    queues: &'py PyList,
+    client: Option<PyObject>,
+) -> PyResult<&'py PyAny> {
+    let subscribers: Vec<Subscriber> = queues.extract()?;
+    let records = read_records(&path)?;
+    let locals = TaskLocals::with_running_loop(py)?.copy_context(py)?;
+    let client = client.unwrap_or_else(|| py.None());
+    py_future(py, async move {
+        let mut count = 0usize;
+        for record in records {
+            let event = record.event.into_event(record.uin);
+            let targets: Vec<&Subscriber> = subscribers
+                .iter()
+                .filter(|sub| sub.accepts(&event, record.uin))
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            let py_event = py_try(|py| event.into_py_event(py, client.clone_ref(py)))?;
+            try_join_all(targets.iter().map(|sub| {
+                sub.consumer
+                    .put(&locals, py_use(|py| py_event.clone_ref(py)))
+            }))
+            .await?;
+            count += 1;
+        }
+        Ok(count)
+    })
+}
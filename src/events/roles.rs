@@ -0,0 +1,67 @@
+//! 群成员权限的缓存。
+//!
+//! 消息本身不携带发送者的群成员权限，这里记录获取群成员列表时得到的权限，用于补全群消息的发送者信息。
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru_time_cache::LruCache;
+
+use super::model::Event;
+
+pub struct MemberRoles {
+    inner: Mutex<LruCache<(i64, i64), &'static str>>,
+}
+
+impl Default for MemberRoles {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(LruCache::with_expiry_duration_and_capacity(
+                Duration::from_secs(3600),
+                65536,
+            )),
+        }
+    }
+}
+
+impl MemberRoles {
+    /// 记录群成员的权限名称。
+    pub fn insert(&self, group_uin: i64, uin: i64, role: &'static str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.insert((group_uin, uin), role);
+        }
+    }
+
+    fn get(&self, group_uin: i64, uin: i64) -> Option<&'static str> {
+        self.inner.lock().ok()?.get(&(group_uin, uin)).copied()
+    }
+
+    fn remove(&self, group_uin: i64, uin: i64) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.remove(&(group_uin, uin));
+        }
+    }
+
+    /// 补全群消息发送者的权限，成员权限变更或离开群时移除对应的记录。
+    pub fn apply(&self, event: &mut Event) {
+        match event {
+            Event::GroupMessage {
+                group, sender_info, ..
+            }
+            | Event::SelfGroupMessage {
+                group, sender_info, ..
+            } => {
+                if sender_info.role.is_none() {
+                    sender_info.role = self.get(*group, sender_info.uin).map(String::from);
+                }
+            }
+            Event::MemberPermissionChange { group, target, .. } => self.remove(*group, *target),
+            Event::MemberLeaveGroup {
+                group_uin,
+                member_uin,
+                ..
+            } => self.remove(*group_uin, *member_uin),
+            _ => {}
+        }
+    }
+}
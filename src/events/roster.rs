@@ -0,0 +1,188 @@
+//! 好友、群与群成员列表的快照。
+//!
+//! 服务器不会推送群名片、专属头衔、群公告与好友备注的变更，也不会补发离线期间的入群与退群事件。
+//! 这里保存定期获取的列表，并与新获取的列表比较以生成对应的事件。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::time::Instant;
+
+use super::model::Event;
+
+/// 群成员的名片与专属头衔，通过事件得知但尚未获取详细信息的成员为 `None`。
+type MemberState = Option<(String, String)>;
+
+#[derive(Default)]
+struct GroupMembers {
+    members: HashMap<i64, MemberState>,
+    /// 最近一次通过事件得知成员变动的时间
+    touched: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Roster {
+    friends: Option<HashMap<i64, String>>,
+    groups: Option<HashMap<i64, String>>,
+    members: HashMap<i64, GroupMembers>,
+}
+
+#[derive(Default)]
+pub struct RosterSnapshot {
+    inner: Mutex<Roster>,
+}
+
+impl RosterSnapshot {
+    /// 比较好友备注，首次获取时只记录快照。
+    pub fn diff_friends(&self, friends: impl IntoIterator<Item = (i64, String)>) -> Vec<Event> {
+        let Ok(mut roster) = self.inner.lock() else {
+            return vec![];
+        };
+        let friends: HashMap<i64, String> = friends.into_iter().collect();
+        let mut events = vec![];
+        if let Some(old) = &roster.friends {
+            for (&uin, remark) in &friends {
+                if let Some(old_remark) = old.get(&uin) && old_remark != remark {
+                    events.push(Event::FriendRemarkChange {
+                        friend_uin: uin,
+                        old: old_remark.clone(),
+                        new: remark.clone(),
+                    });
+                }
+            }
+        }
+        roster.friends = Some(friends);
+        events
+    }
+
+    /// 比较群公告，首次获取时只记录快照，同时移除已不在的群的成员快照。
+    pub fn diff_groups(&self, groups: impl IntoIterator<Item = (i64, String)>) -> Vec<Event> {
+        let Ok(mut roster) = self.inner.lock() else {
+            return vec![];
+        };
+        let groups: HashMap<i64, String> = groups.into_iter().collect();
+        let mut events = vec![];
+        if let Some(old) = &roster.groups {
+            for (&uin, memo) in &groups {
+                if let Some(old_memo) = old.get(&uin) && old_memo != memo {
+                    events.push(Event::GroupMemoChange {
+                        group: uin,
+                        old: old_memo.clone(),
+                        new: memo.clone(),
+                    });
+                }
+            }
+        }
+        roster.members.retain(|uin, _| groups.contains_key(uin));
+        roster.groups = Some(groups);
+        events
+    }
+
+    /// 比较群成员列表，`members` 为 `(QQ 号, 群名片, 专属头衔)`，首次获取时只记录快照。
+    ///
+    /// `fetched_at` 为开始获取列表的时间，此后若通过事件得知了成员变动，列表可能已过时，本次比较会被放弃。
+    pub fn diff_members(
+        &self,
+        group: i64,
+        members: impl IntoIterator<Item = (i64, String, String)>,
+        fetched_at: Instant,
+    ) -> Vec<Event> {
+        let Ok(mut roster) = self.inner.lock() else {
+            return vec![];
+        };
+        let members: HashMap<i64, MemberState> = members
+            .into_iter()
+            .map(|(uin, card, title)| (uin, Some((card, title))))
+            .collect();
+        let Some(snapshot) = roster.members.get_mut(&group) else {
+            roster.members.insert(
+                group,
+                GroupMembers {
+                    members,
+                    touched: None,
+                },
+            );
+            return vec![];
+        };
+        if snapshot.touched.is_some_and(|touched| touched >= fetched_at) {
+            return vec![];
+        }
+        let mut events = vec![];
+        for (&uin, state) in &members {
+            let Some(old) = snapshot.members.get(&uin) else {
+                events.push(Event::OfflineMemberJoin { group, member: uin });
+                continue;
+            };
+            let (Some((old_card, old_title)), Some((card, title))) = (old, state) else {
+                continue;
+            };
+            if old_card != card {
+                events.push(Event::MemberCardChange {
+                    group,
+                    member: uin,
+                    old: old_card.clone(),
+                    new: card.clone(),
+                });
+            }
+            if old_title != title {
+                events.push(Event::MemberTitleChange {
+                    group,
+                    member: uin,
+                    old: old_title.clone(),
+                    new: title.clone(),
+                });
+            }
+        }
+        for &uin in snapshot.members.keys() {
+            if !members.contains_key(&uin) {
+                events.push(Event::OfflineMemberLeave { group, member: uin });
+            }
+        }
+        snapshot.members = members;
+        events
+    }
+
+    /// 根据实时事件更新快照，避免将其重复报告为离线期间的变动。
+    pub fn observe(&self, event: &Event) {
+        let Ok(mut roster) = self.inner.lock() else {
+            return;
+        };
+        match event {
+            Event::NewMember { group, member } => {
+                if let Some(snapshot) = roster.members.get_mut(group) {
+                    snapshot.members.entry(*member).or_insert(None);
+                    snapshot.touched = Some(Instant::now());
+                }
+            }
+            Event::MemberLeaveGroup {
+                group_uin,
+                member_uin,
+                ..
+            } => {
+                if let Some(snapshot) = roster.members.get_mut(group_uin) {
+                    snapshot.members.remove(member_uin);
+                    snapshot.touched = Some(Instant::now());
+                }
+            }
+            Event::BotLeaveGroup { group_uin }
+            | Event::BotKicked { group_uin, .. }
+            | Event::GroupDisband { group_uin, .. } => {
+                roster.members.remove(group_uin);
+                if let Some(groups) = &mut roster.groups {
+                    groups.remove(group_uin);
+                }
+            }
+            Event::NewFriend { friend } => {
+                if let Some(friends) = &mut roster.friends {
+                    friends.insert(friend.uin, friend.remark.clone());
+                }
+            }
+            Event::FriendDeleted { friend_uin } => {
+                if let Some(friends) = &mut roster.friends {
+                    friends.remove(friend_uin);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! 当前客户端发送过的消息。
+//!
+//! 自身账号发送的消息会被服务器同步回来，这里记录当前客户端发送的消息的随机序列号，
+//! 以区分当前客户端发送的消息与其他设备发送的消息。
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru_time_cache::LruCache;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// 等待发送回执的最长时间，服务器同步回来的消息可能早于发送回执到达。
+const RECEIPT_GRACE: Duration = Duration::from_millis(500);
+
+/// `(是否为群消息, 群号或好友 QQ 号, 随机序列号)`
+type SentKey = (bool, i64, i32);
+
+pub struct SentMessages {
+    inner: Mutex<LruCache<SentKey, ()>>,
+    notify: Notify,
+}
+
+impl Default for SentMessages {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(LruCache::with_expiry_duration_and_capacity(
+                Duration::from_secs(600),
+                4096,
+            )),
+            notify: Notify::new(),
+        }
+    }
+}
+
+impl SentMessages {
+    /// 记录当前客户端发送的消息。
+    pub fn insert(&self, group: bool, target: i64, rands: &[i32]) {
+        if let Ok(mut inner) = self.inner.lock() {
+            for &rand in rands {
+                inner.insert((group, target, rand), ());
+            }
+        }
+        self.notify.notify_waiters();
+    }
+
+    fn contains(&self, key: &SentKey) -> bool {
+        self.inner
+            .lock()
+            .map_or(false, |mut inner| inner.get(key).is_some())
+    }
+
+    /// 检查消息是否由当前客户端发送，未找到时会短暂等待发送回执。
+    pub async fn was_sent(&self, group: bool, target: i64, rand: i32) -> bool {
+        let key = (group, target, rand);
+        let deadline = Instant::now() + RECEIPT_GRACE;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.contains(&key) {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return false;
+            }
+        }
+    }
+}
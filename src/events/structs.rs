@@ -0,0 +1,354 @@
+//! 投递给事件队列的事件类型。
+//!
+//! 每个事件都支持 `match` 语句的位置模式，也可以通过 `event["field"]` 按字段名访问以兼容旧版的字典事件。
+
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::*;
+use pyo3_repr::PyRepr;
+
+use super::{MessageSender, MessageSource};
+use crate::client::structs::Friend;
+
+macro_rules! py_event {
+    ($(#[doc = $doc:literal])* $name:ident { $($field:ident : $ty:ty),* $(,)? }) => {
+        $(#[doc = $doc])*
+        #[pyclass(get_all, module = "ichika.core")]
+        #[derive(PyRepr, Clone)]
+        pub struct $name {
+            #[py_debug(skip)]
+            pub client: PyObject,
+            $(pub $field: $ty,)*
+        }
+
+        #[pymethods]
+        impl $name {
+            #[classattr]
+            fn __match_args__(py: Python) -> Py<PyTuple> {
+                let names: &[&str] = &[$(stringify!($field)),*];
+                PyTuple::new(py, names).into()
+            }
+
+            #[getter]
+            fn type_name(&self) -> &'static str {
+                stringify!($name)
+            }
+
+            fn __getitem__(slf: &PyCell<Self>, key: &str) -> PyResult<PyObject> {
+                slf.getattr(key)
+                    .map(|v| v.into_py(slf.py()))
+                    .map_err(|_| PyKeyError::new_err(key.to_owned()))
+            }
+        }
+    };
+}
+
+py_event!(
+    /// 群消息
+    GroupMessage {
+        source: MessageSource,
+        content: PyObject,
+        group: i64,
+        sender: i64,
+        group_name: String,
+        sender_info: MessageSender,
+    }
+);
+
+py_event!(
+    /// 群消息撤回
+    GroupRecallMessage {
+        time: PyObject,
+        group: i64,
+        author: i64,
+        operator: i64,
+        seq: i32,
+        content: PyObject,
+    }
+);
+
+py_event!(
+    /// 好友消息
+    FriendMessage {
+        source: MessageSource,
+        content: PyObject,
+        sender: i64,
+        sender_info: MessageSender,
+    }
+);
+
+py_event!(
+    /// 好友消息撤回
+    FriendRecallMessage {
+        time: PyObject,
+        author: i64,
+        seq: i32,
+        content: PyObject,
+    }
+);
+
+py_event!(
+    /// 群临时会话消息
+    TempMessage {
+        source: MessageSource,
+        content: PyObject,
+        group: i64,
+        sender: i64,
+        sender_info: MessageSender,
+    }
+);
+
+py_event!(
+    /// 本账号发送的群消息，包括其他设备发送后同步的消息
+    SelfGroupMessage {
+        source: MessageSource,
+        content: PyObject,
+        group: i64,
+        sender: i64,
+        group_name: String,
+        sender_info: MessageSender,
+        from_client: bool,
+    }
+);
+
+py_event!(
+    /// 本账号发送的好友消息，包括其他设备发送后同步的消息
+    SelfFriendMessage {
+        source: MessageSource,
+        content: PyObject,
+        sender: i64,
+        target: i64,
+        sender_info: MessageSender,
+        from_client: bool,
+    }
+);
+
+py_event!(
+    /// 群戳一戳
+    GroupNudge {
+        group: i64,
+        sender: i64,
+        receiver: i64,
+    }
+);
+
+py_event!(
+    /// 好友戳一戳
+    FriendNudge { sender: i64 }
+);
+
+py_event!(
+    /// 新好友
+    NewFriend { friend: Friend }
+);
+
+py_event!(
+    /// 新群成员
+    NewMember { group: i64, member: i64 }
+);
+
+py_event!(
+    /// 群成员离开
+    MemberLeaveGroup {
+        group_uin: i64,
+        member_uin: i64,
+        operator_uin: Option<i64>,
+        kind: String,
+    }
+);
+
+py_event!(
+    /// 机器人加入群
+    BotJoinGroup { group_uin: i64 }
+);
+
+py_event!(
+    /// 机器人主动退群
+    BotLeaveGroup { group_uin: i64 }
+);
+
+py_event!(
+    /// 机器人被移出群
+    BotKicked {
+        group_uin: i64,
+        operator_uin: i64,
+    }
+);
+
+py_event!(
+    /// 群解散
+    GroupDisband {
+        group_uin: i64,
+        operator_uin: i64,
+    }
+);
+
+py_event!(
+    /// 好友被删除
+    FriendDeleted { friend_uin: i64 }
+);
+
+py_event!(
+    /// 全体禁言状态变更
+    GroupMute {
+        group: i64,
+        operator: i64,
+        status: bool,
+    }
+);
+
+py_event!(
+    /// 群成员禁言，`duration` 为 `False` 时表示解除禁言
+    MemberMute {
+        group: i64,
+        operator: i64,
+        target: i64,
+        duration: PyObject,
+    }
+);
+
+py_event!(
+    /// 群成员权限变更
+    MemberPermissionChange {
+        group: i64,
+        target: i64,
+        permission: u8,
+    }
+);
+
+py_event!(
+    /// 群信息变更
+    GroupInfoUpdate {
+        group: i64,
+        operator: i64,
+        info: PyObject,
+    }
+);
+
+py_event!(
+    /// 群名片变更
+    MemberCardChange {
+        group: i64,
+        member: i64,
+        old: String,
+        new: String,
+    }
+);
+
+py_event!(
+    /// 群专属头衔变更
+    MemberTitleChange {
+        group: i64,
+        member: i64,
+        old: String,
+        new: String,
+    }
+);
+
+py_event!(
+    /// 群公告变更
+    GroupMemoChange {
+        group: i64,
+        old: String,
+        new: String,
+    }
+);
+
+py_event!(
+    /// 好友备注变更
+    FriendRemarkChange {
+        friend_uin: i64,
+        old: String,
+        new: String,
+    }
+);
+
+py_event!(
+    /// 离线期间加入群的成员
+    OfflineMemberJoin { group: i64, member: i64 }
+);
+
+py_event!(
+    /// 离线期间离开群的成员
+    OfflineMemberLeave { group: i64, member: i64 }
+);
+
+py_event!(
+    /// 好友申请
+    NewFriendRequest {
+        seq: i64,
+        uin: i64,
+        nickname: String,
+        message: String,
+    }
+);
+
+py_event!(
+    /// 入群申请
+    JoinGroupRequest {
+        seq: i64,
+        time: PyObject,
+        group_uin: i64,
+        group_name: String,
+        request_uin: i64,
+        request_nickname: String,
+        suspicious: bool,
+        invitor_uin: Option<i64>,
+        invitor_nickname: Option<String>,
+    }
+);
+
+py_event!(
+    /// 入群邀请
+    JoinGroupInvitation {
+        seq: i64,
+        time: PyObject,
+        group_uin: i64,
+        group_name: String,
+        invitor_uin: i64,
+        invitor_nickname: String,
+    }
+);
+
+py_event!(
+    /// 未知事件
+    UnknownEvent { internal_repr: String }
+);
+
+py_event!(
+    /// 客户端登录成功
+    Connected {}
+);
+
+py_event!(
+    /// 客户端断开连接
+    Disconnected { reason: String }
+);
+
+py_event!(
+    /// 客户端正在等待重连
+    Reconnecting {
+        attempt: u32,
+        delay: PyObject,
+    }
+);
+
+py_event!(
+    /// 客户端重连成功
+    Reconnected {}
+);
+
+py_event!(
+    /// 客户端已下线且不会再重连
+    LoggedOut { error: Option<String> }
+);
+
+py_event!(
+    /// 被其他设备登录挤下线
+    KickedOffline { title: String, tips: String }
+);
+
+py_event!(
+    /// 被服务器强制下线
+    MSFOffline { title: String, info: String }
+);
@@ -0,0 +1,97 @@
+//! 等待下一个符合条件的事件。
+//!
+//! 等待者按注册顺序在投递给事件队列之前依次求值，每个等待者只接收一个事件。
+//! 设置了 `consume` 的等待者接收事件后，之后的等待者与事件队列都不会再看到该事件。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use super::filter::EventFilter;
+use super::model::Event;
+
+struct Waiter {
+    id: u64,
+    filter: EventFilter,
+    consume: bool,
+    tx: oneshot::Sender<Event>,
+}
+
+#[derive(Default)]
+pub struct Waiters {
+    inner: Mutex<Vec<Waiter>>,
+    next_id: AtomicU64,
+}
+
+/// 等待结束时移除尚未收到事件的等待者。
+struct Unregister<'a> {
+    waiters: &'a Waiters,
+    id: u64,
+}
+
+impl Drop for Unregister<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut inner) = self.waiters.inner.lock() {
+            inner.retain(|waiter| waiter.id != self.id);
+        }
+    }
+}
+
+impl Waiters {
+    /// 注册等待者，返回的接收端需要交给 [`Waiters::wait`]。
+    pub fn register(&self, filter: EventFilter, consume: bool) -> (u64, oneshot::Receiver<Event>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.push(Waiter {
+                id,
+                filter,
+                consume,
+                tx,
+            });
+        }
+        (id, rx)
+    }
+
+    /// 等待事件，超时或被取消时移除等待者。
+    pub async fn wait(
+        &self,
+        id: u64,
+        mut rx: oneshot::Receiver<Event>,
+        timeout: Option<Duration>,
+    ) -> Option<Event> {
+        let unregister = Unregister { waiters: self, id };
+        match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, &mut rx).await {
+                Ok(res) => res.ok(),
+                Err(_) => {
+                    // 先移除等待者，再取出超时的同时送达的事件，避免被消费的事件丢失
+                    drop(unregister);
+                    rx.try_recv().ok()
+                }
+            },
+            None => rx.await.ok(),
+        }
+    }
+
+    /// 将事件交给匹配的等待者，返回事件是否被消费。
+    pub fn offer(&self, event: &Event, self_uin: i64) -> bool {
+        let Ok(mut inner) = self.inner.lock() else {
+            return false;
+        };
+        let mut i = 0;
+        while i < inner.len() {
+            if !inner[i].filter.matches(event, self_uin) {
+                i += 1;
+                continue;
+            }
+            let waiter = inner.remove(i);
+            if waiter.tx.send(event.clone()).is_ok() && waiter.consume {
+                return true;
+            }
+        }
+        false
+    }
+}
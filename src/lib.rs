@@ -46,6 +46,21 @@ pub fn core(py: Python, m: &PyModule) -> PyResult<()> {
         loguru::getframe,
         message::elements::face_id_from_name,
         message::elements::face_name_from_id,
+        message::face::face_catalogue,
+        message::face::face_info,
+        message::face::face_info_from_name,
+        message::qr::decode_qrcodes,
+        message::qr::make_qrcode_image,
+        message::transcript::render_transcript,
+        message::transcript::load_transcript,
+        message::cq::elements_to_cq,
+        message::cq::cq_to_elements,
+        message::wire::elements_to_json,
+        message::wire::elements_from_json,
+        events::model::event_to_json,
+        events::model::event_from_json,
+        events::model::wire_schema,
+        events::record::replay_events,
         login::password_login,
         login::qrcode_login
     );
@@ -62,7 +77,57 @@ pub fn core(py: Python, m: &PyModule) -> PyResult<()> {
         client::structs::OCRResult,
         client::structs::OCRText,
         client::structs::Profile,
-        events::MessageSource
+        events::MessageSource,
+        events::MessageSender,
+        events::Subscription,
+        events::filter::EventFilter,
+        events::dispatch::DispatchPolicy,
+        events::dead_letter::FailedDelivery,
+        events::history::CachedMessage,
+        events::archive::MessageArchive,
+        events::structs::GroupMessage,
+        events::structs::GroupRecallMessage,
+        events::structs::FriendMessage,
+        events::structs::FriendRecallMessage,
+        events::structs::TempMessage,
+        events::structs::SelfGroupMessage,
+        events::structs::SelfFriendMessage,
+        events::structs::GroupNudge,
+        events::structs::FriendNudge,
+        events::structs::NewFriend,
+        events::structs::NewMember,
+        events::structs::MemberLeaveGroup,
+        events::structs::BotJoinGroup,
+        events::structs::BotLeaveGroup,
+        events::structs::BotKicked,
+        events::structs::GroupDisband,
+        events::structs::FriendDeleted,
+        events::structs::GroupMute,
+        events::structs::MemberMute,
+        events::structs::MemberPermissionChange,
+        events::structs::GroupInfoUpdate,
+        events::structs::MemberCardChange,
+        events::structs::MemberTitleChange,
+        events::structs::GroupMemoChange,
+        events::structs::FriendRemarkChange,
+        events::structs::OfflineMemberJoin,
+        events::structs::OfflineMemberLeave,
+        events::structs::NewFriendRequest,
+        events::structs::JoinGroupRequest,
+        events::structs::JoinGroupInvitation,
+        events::structs::UnknownEvent,
+        events::structs::Connected,
+        events::structs::Disconnected,
+        events::structs::Reconnecting,
+        events::structs::Reconnected,
+        events::structs::LoggedOut,
+        events::structs::KickedOffline,
+        events::structs::MSFOffline,
+        message::elements::MarketFaceItem,
+        message::elements::MarketFacePack,
+        message::elements::SealedMarketFace,
+        message::face::FaceInfo,
+        message::qr::DecodedQRCode
     );
     loguru::init(m)?;
     Ok(())
@@ -1,6 +1,8 @@
 mod connector;
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use connector::IchikaConnector;
 use pyo3::exceptions::*;
@@ -21,8 +23,12 @@ use ricq::{
 };
 use tokio::task::JoinHandle;
 
-use crate::events::PyHandler;
+use crate::events::archive::MessageArchive;
+use crate::events::model::Event;
+use crate::events::record::EventRecorder;
+use crate::events::{EventDispatcher, PyHandler};
 use crate::exc::{MapPyErr, RICQError};
+use crate::message::qr::{decode_grids, encode_modules};
 use crate::utils::{partial, py_bytes, py_client_refs, py_future, py_try, py_use};
 use crate::{exc, import_call, PyRet};
 
@@ -187,15 +193,37 @@ impl TokenRW {
     }
 }
 
+#[allow(clippy::too_many_arguments, reason = "Mirrors the Python login signatures")]
 fn parse_login_args<'py>(
     py: Python<'py>,
     uin: i64,
     protocol: String,
     store: &'py PyAny,
     queues: &'py PyList,
+    dedup_window: Option<f64>,
+    record: Option<PathBuf>,
+    archive: Option<PathBuf>,
+    dead_letter: Option<PyObject>,
 ) -> PyResult<(Version, PyHandler, Device, TokenRW, TaskLocals)> {
     let task_locals = TaskLocals::with_running_loop(py)?.copy_context(py)?; // Necessary since retrieving task locals at handling time is already insufficient
-    let handler = PyHandler::new(queues.into_py(py), task_locals.clone(), uin);
+    let dedup_window = dedup_window
+        .filter(|secs| *secs > 0.0)
+        .map(Duration::try_from_secs_f64)
+        .transpose()
+        .map_err(|e| PyValueError::new_err(format!("无效的去重时间窗口: {e}")))?;
+    let recorder = record
+        .map(|path| EventRecorder::open(uin, path))
+        .transpose()?;
+    let archive = archive.map(MessageArchive::open).transpose()?;
+    let handler = PyHandler::new(
+        queues.extract()?,
+        task_locals.clone(),
+        uin,
+        dedup_window,
+        recorder,
+        archive,
+        dead_letter,
+    );
 
     let get_token = partial(py).call1((store.getattr("get_token")?, uin, &protocol))?;
     let write_token = partial(py).call1((store.getattr("write_token")?, uin, &protocol))?;
@@ -241,10 +269,9 @@ async fn invoke_cb(
 pub async fn reconnect(
     client: &Arc<Client>,
     token_rw: &TokenRW,
+    dispatcher: &Arc<EventDispatcher>,
 ) -> PyResult<Option<JoinHandle<()>>> {
-    use std::time::Duration;
-
-    use backon::{ExponentialBuilder, Retryable as _};
+    use backon::{BackoffBuilder as _, ExponentialBuilder};
 
     let uin = client.uin().await;
 
@@ -287,17 +314,32 @@ pub async fn reconnect(
         tracing::info!("客户端重连成功");
         Ok(Some(alive))
     };
-    retry_closure
-        .retry(&retry_builder)
-        .notify(|e, dur: Duration| {
-            tracing::error!(
-                "客户端 {} 重连失败，原因：{}，将在 {:.2} 秒后重试",
-                uin,
-                e,
-                dur.as_secs_f64()
-            );
-        })
-        .await
+    // 等待重连事件投递完成后再重试，保证与之后的 `Reconnected` 与 `Disconnected` 事件的顺序
+    let mut backoff = retry_builder.build();
+    let mut attempt = 0;
+    loop {
+        let e = match retry_closure().await {
+            Ok(alive) => return Ok(alive),
+            Err(e) => e,
+        };
+        let Some(dur) = backoff.next() else {
+            return Err(e);
+        };
+        attempt += 1;
+        tracing::error!(
+            "客户端 {} 重连失败，原因：{}，将在 {:.2} 秒后重试",
+            uin,
+            e,
+            dur.as_secs_f64()
+        );
+        dispatcher
+            .dispatch(Event::Reconnecting {
+                attempt,
+                delay: dur.as_secs_f64(),
+            })
+            .await;
+        tokio::time::sleep(dur).await;
+    }
 }
 
 async fn make_password_login_req(
@@ -458,7 +500,12 @@ async fn after_login(client: &Arc<Client>) -> PyResult<()> {
     Ok(())
 }
 
-async fn post_login(client: Arc<Client>, alive: JoinHandle<()>, token_rw: TokenRW) -> PyRet {
+async fn post_login(
+    client: Arc<Client>,
+    alive: JoinHandle<()>,
+    token_rw: TokenRW,
+    dispatcher: Arc<EventDispatcher>,
+) -> PyRet {
     after_login(&client).await?;
 
     token_rw.set(&client).await?;
@@ -468,15 +515,32 @@ async fn post_login(client: Arc<Client>, alive: JoinHandle<()>, token_rw: TokenR
         client,
         alive: Arc::new(std::sync::Mutex::new(Some(alive))),
         token_rw,
+        dispatcher: dispatcher.clone(),
     };
-    py_try(|py| {
+    let client = py_try(|py| {
         let client = import_call!(py, "ichika.client" => "Client" => init)?.into_py(py);
         py_client_refs(py).set_item(uin, client.clone_ref(py))?;
         Ok(client)
-    })
+    })?;
+    dispatcher.dispatch(Event::Connected).await;
+    Ok(client)
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    uin,
+    credential,
+    use_sms,
+    protocol,
+    store,
+    queues,
+    login_callbacks,
+    *,
+    dedup_window = Some(60.0),
+    record = None,
+    archive = None,
+    dead_letter = None
+))]
 #[allow(clippy::too_many_arguments, reason = "Required for Python binding")]
 pub fn password_login<'py>(
     py: Python<'py>,
@@ -487,9 +551,23 @@ pub fn password_login<'py>(
     store: &'py PyAny,
     queues: &'py PyList,       // List[asyncio.Queue[Event]]
     login_callbacks: PyObject, // PasswordLoginCallbacks
+    dedup_window: Option<f64>,
+    record: Option<PathBuf>,
+    archive: Option<PathBuf>,
+    dead_letter: Option<PyObject>,
 ) -> PyResult<&'py PyAny> {
-    let (protocol, handler, device, token_rw, locals) =
-        parse_login_args(py, uin, protocol, store, queues)?;
+    let (protocol, handler, device, token_rw, locals) = parse_login_args(
+        py,
+        uin,
+        protocol,
+        store,
+        queues,
+        dedup_window,
+        record,
+        archive,
+        dead_letter,
+    )?;
+    let dispatcher = handler.dispatcher();
     py_future(py, async move {
         let (client, alive) = prepare_client(device, protocol.clone(), handler).await?;
         if !token_rw.try_login(&client).await? {
@@ -499,42 +577,19 @@ pub fn password_login<'py>(
                 .await?;
         }
 
-        Ok(post_login(client, alive, token_rw).await?)
+        Ok(post_login(client, alive, token_rw, dispatcher).await?)
     })
 }
 
 fn parse_qrcode(qrcode: &bytes::Bytes) -> PyResult<Vec<Vec<bool>>> {
-    let qrcode = image::load_from_memory(qrcode)
-        .map_err(|e| PyValueError::new_err(format!("加载二维码图像出现错误: {e:?}")))?
-        .to_luma8();
-    let mut qrcode = rqrr::PreparedImage::prepare(qrcode);
-    let grids = qrcode.detect_grids();
+    let grids = decode_grids(qrcode)?;
     if grids.len() != 1 {
         return Err(PyValueError::new_err(format!(
             "无法识别二维码, 发现 {} 个二维码",
             grids.len()
         )));
     }
-    let (_, content) = grids[0]
-        .decode()
-        .map_err(|e| PyValueError::new_err(format!("解码二维码出现错误: {e:?}")))?;
-    let qrcode = qrcode::QrCode::new(content)
-        .map_err(|e| PyValueError::new_err(format!("生成二维码数据出现错误: {e:?}")))?;
-
-    let width = qrcode.width();
-    Ok(qrcode
-        .into_colors()
-        .chunks(width)
-        .map(|chunk| {
-            chunk
-                .iter()
-                .map(|c| match c {
-                    qrcode::Color::Light => true,
-                    qrcode::Color::Dark => false,
-                })
-                .collect()
-        })
-        .collect())
+    encode_modules(grids[0].0.as_bytes(), qrcode::EcLevel::M)
 }
 
 async fn qrcode_login_process(
@@ -609,6 +664,19 @@ async fn qrcode_login_process(
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    uin,
+    protocol,
+    store,
+    queues,
+    login_callbacks,
+    *,
+    dedup_window = Some(60.0),
+    record = None,
+    archive = None,
+    dead_letter = None
+))]
+#[allow(clippy::too_many_arguments, reason = "Required for Python binding")]
 pub fn qrcode_login<'py>(
     py: Python<'py>,
     uin: i64,
@@ -616,9 +684,23 @@ pub fn qrcode_login<'py>(
     store: &'py PyAny,
     queues: &'py PyList,       // List[asyncio.Queue[Event]]
     login_callbacks: PyObject, // QRCodeLoginCallbacks
+    dedup_window: Option<f64>,
+    record: Option<PathBuf>,
+    archive: Option<PathBuf>,
+    dead_letter: Option<PyObject>,
 ) -> PyResult<&'py PyAny> {
-    let (protocol, handler, device, token_rw, locals) =
-        parse_login_args(py, uin, protocol, store, queues)?;
+    let (protocol, handler, device, token_rw, locals) = parse_login_args(
+        py,
+        uin,
+        protocol,
+        store,
+        queues,
+        dedup_window,
+        record,
+        archive,
+        dead_letter,
+    )?;
+    let dispatcher = handler.dispatcher();
     py_future(py, async move {
         let (client, alive) = prepare_client(device, protocol.clone(), handler).await?;
         if !token_rw.try_login(&client).await? {
@@ -633,6 +715,6 @@ pub fn qrcode_login<'py>(
             qrcode_login_process(&locals, &client, uin, handle_getter, interval).await?;
         }
 
-        Ok(post_login(client, alive, token_rw).await?)
+        Ok(post_login(client, alive, token_rw, dispatcher).await?)
     })
 }
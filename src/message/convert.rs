@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::*;
@@ -7,6 +9,8 @@ use ricq::structs::ForwardMessage;
 use ricq_core::msg::elem::{At, Dice, Face, FingerGuessing, Text};
 
 use super::elements::*;
+use super::face::{self, SuperFace};
+use super::wire::WireChain;
 use crate::utils::datetime_from_ts;
 use crate::{dict, static_py_fn};
 
@@ -76,7 +80,7 @@ pub fn serialize_element(py: Python, e: RQElem) -> PyResult<Option<&PyDict>> {
             }
         }
         RQElem::MarketFace(m) => {
-            let f = SealedMarketFace { inner: m };
+            let f = SealedMarketFace::new(m);
             dict! {py,
             type: "MarketFace",
             raw: f.into_py(py)
@@ -138,18 +142,6 @@ pub fn serialize_element(py: Python, e: RQElem) -> PyResult<Option<&PyDict>> {
     Ok(Some(data))
 }
 
-// Reply + Bot Image = skip message ???
-// Needs testing
-pub fn serialize_reply(py: Python, reply: Reply) -> PyResult<&PyDict> {
-    Ok(dict! {py,
-        type: "Reply",
-        seq: reply.reply_seq,
-        sender: reply.sender,
-        time: datetime_from_ts(py, reply.time)?,
-        content: reply.elements.to_string()
-    })
-}
-
 pub fn render_forward(file_name: &str, res_id: &str, preview: &str, summary: &str) -> String {
     format!(
         r##"<?xml version='1.0' encoding='UTF-8'?><msg serviceID="35" templateID="1" action="viewMultiMsg" brief="[聊天记录]"  m_resid="{res_id}" m_fileName="{file_name}" tSum="3" sourceMsgId="0" url="" flag="3" adverSign="0" multiMsgFlag="0"><item layout="1"><title color="#000000" size="34">群聊的聊天记录</title>{preview}<hr></hr><summary size="26" color="#808080">{summary}</summary></item><source name="聊天记录"></source></msg>"##
@@ -180,22 +172,7 @@ pub fn serialize_forward(py: Python, forward: ForwardMessage) -> PyResult<&PyDic
 }
 
 pub fn serialize_message_chain(py: Python, chain: MessageChain) -> PyResult<Py<PyList>> {
-    use ricq_core::msg::MessageElem as BaseElem;
-    let res = PyList::empty(py);
-    for e in chain.0 {
-        match e {
-            BaseElem::SrcMsg(reply) => {
-                res.append(serialize_reply(py, reply.into())?)?;
-            }
-            BaseElem::AnonGroupMsg(_) => {} // Anonymous information, TODO
-            elem => {
-                if let Some(data) = serialize_element(py, RQElem::from(elem))? {
-                    res.append(data)?;
-                }
-            }
-        }
-    }
-    Ok(res.into_py(py))
+    Ok(WireChain::from(chain).into_py_list(py)?.into_py(py))
 }
 
 static_py_fn!(
@@ -205,6 +182,13 @@ static_py_fn!(
     ["_deserialize_message"]
 );
 
+static_py_fn!(
+    py_serialize,
+    __py_serialize_cell,
+    "ichika.message",
+    ["_serialize_message"]
+);
+
 pub fn serialize_as_py_chain(py: Python, chain: MessageChain) -> PyResult<PyObject> // PyMessageChain
 {
     let py_fn: &PyAny = py_deserialize(py);
@@ -213,7 +197,12 @@ pub fn serialize_as_py_chain(py: Python, chain: MessageChain) -> PyResult<PyObje
         .into_py(py))
 }
 
-pub fn deserialize_element(chain: &mut MessageChain, ident: &str, store: &PyAny) -> PyResult<()> {
+pub fn deserialize_element(
+    chain: &mut MessageChain,
+    ident: &str,
+    store: &PyAny,
+    at_displays: &HashMap<i64, String>,
+) -> PyResult<()> {
     match ident {
         "AtAll" => chain.push(At {
             target: 0,
@@ -225,6 +214,7 @@ pub fn deserialize_element(chain: &mut MessageChain, ident: &str, store: &PyAny)
                 .get_item("display")?
                 .extract::<String>()
                 .ok()
+                .or_else(|| at_displays.get(&target).cloned())
                 .unwrap_or_else(|| format!("@{target}"));
             chain.push(At { target, display });
         }
@@ -246,7 +236,30 @@ pub fn deserialize_element(chain: &mut MessageChain, ident: &str, store: &PyAny)
             chain.push(store.get_item("raw")?.extract::<SealedMarketFace>()?.inner);
         }
         "Face" => {
-            chain.push(Face::new(store.get_item("index")?.extract::<i32>()?));
+            let index = store.get_item("index")?.extract::<i32>()?;
+            match face::pack_small_face(index) {
+                Some(elems) => chain.0.extend(elems),
+                None => chain.push(Face::new(index)),
+            }
+        }
+        "SuperFace" => {
+            let index = store.get_item("index")?.extract::<i32>()?;
+            let name = store
+                .get_item("name")?
+                .extract::<String>()
+                .ok()
+                .unwrap_or_else(|| face::face_name(index));
+            let large = store.get_item("large")?.extract::<bool>()?;
+            let sticker_type = store.get_item("sticker_type")?.extract::<Option<u32>>()?;
+            chain.0.extend(
+                SuperFace {
+                    index,
+                    name,
+                    large,
+                    sticker_type,
+                }
+                .pack(),
+            );
         }
         "Image" => {
             let raw = store.get_item("raw")?;
@@ -296,6 +309,14 @@ pub fn deserialize_element(chain: &mut MessageChain, ident: &str, store: &PyAny)
 }
 
 pub fn deserialize_message_chain(list: &PyList) -> PyResult<MessageChain> {
+    deserialize_message_chain_with(list, &HashMap::new())
+}
+
+/// 反序列化消息链，未指定显示文本的 `At` 元素使用 `at_displays` 中的值。
+pub fn deserialize_message_chain_with(
+    list: &PyList,
+    at_displays: &HashMap<i64, String>,
+) -> PyResult<MessageChain> {
     let mut chain: MessageChain = MessageChain::new(Vec::new());
     for elem_d in list {
         let elem_d: &PyDict = elem_d.downcast()?;
@@ -303,7 +324,27 @@ pub fn deserialize_message_chain(list: &PyList) -> PyResult<MessageChain> {
             .get_item("type")
             .ok_or_else(|| PyValueError::new_err("Missing `type`!"))?
             .extract::<&str>()?;
-        deserialize_element(&mut chain, name, elem_d.into())?;
+        deserialize_element(&mut chain, name, elem_d.into(), at_displays)?;
     }
     Ok(chain)
 }
+
+/// 获取消息链中未指定显示文本的 `At` 元素的目标。
+pub fn unresolved_at_targets(list: &PyList) -> PyResult<Vec<i64>> {
+    let mut targets = Vec::new();
+    for elem_d in list {
+        let elem_d: &PyDict = elem_d.downcast()?;
+        let is_at = match elem_d.get_item("type") {
+            Some(ident) => ident.extract::<&str>()? == "At",
+            None => false,
+        };
+        let has_display = elem_d.get_item("display").map_or(false, |d| !d.is_none());
+        if is_at && !has_display {
+            let target = elem_d
+                .get_item("target")
+                .ok_or_else(|| PyValueError::new_err("Missing `target`!"))?;
+            targets.push(target.extract::<i64>()?);
+        }
+    }
+    Ok(targets)
+}
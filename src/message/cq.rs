@@ -0,0 +1,432 @@
+//! CQ 码与消息元素字典之间的转换。
+//!
+//! 转义规则与 go-cqhttp 一致：文本中转义 `&`、`[`、`]`，参数值中额外转义 `,`。
+//! 无法识别的 CQ 码会被转换为 `Unknown` 元素，CQ 码类型保存在 `cq_type` 中，
+//! 原始参数保存在 `params` 中，转换回 CQ 码时保持原样。
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::*;
+
+use super::elements::SealedMarketFace;
+use super::face::{self, FaceKind};
+use crate::dict;
+use crate::utils::{datetime_from_ts, dict_item as get};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CqSegment {
+    Text(String),
+    Code {
+        kind: String,
+        /// 参数名与参数值，没有 `=` 的参数值为 `None`
+        params: Vec<(String, Option<String>)>,
+    },
+}
+
+pub fn escape(text: &str, param: bool) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '[' => res.push_str("&#91;"),
+            ']' => res.push_str("&#93;"),
+            ',' if param => res.push_str("&#44;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+pub fn unescape(text: &str) -> String {
+    text.replace("&#44;", ",")
+        .replace("&#91;", "[")
+        .replace("&#93;", "]")
+        .replace("&amp;", "&")
+}
+
+/// 将 CQ 码字符串拆分为文本与 CQ 码片段。
+///
+/// 没有闭合的 `[CQ:` 会被视为普通文本。
+pub fn parse(text: &str) -> Vec<CqSegment> {
+    let mut res = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[CQ:") {
+        let Some(len) = rest[start..].find(']') else {
+            break;
+        };
+        if start > 0 {
+            res.push(CqSegment::Text(unescape(&rest[..start])));
+        }
+        let mut parts = rest[start + 4..start + len].split(',');
+        let kind = parts.next().unwrap_or_default().to_owned();
+        let params = parts
+            .map(|p| match p.split_once('=') {
+                Some((k, v)) => (k.to_owned(), Some(unescape(v))),
+                None => (p.to_owned(), None),
+            })
+            .collect();
+        res.push(CqSegment::Code { kind, params });
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        res.push(CqSegment::Text(unescape(rest)));
+    }
+    res
+}
+
+pub fn render(segments: &[CqSegment]) -> String {
+    let mut res = String::new();
+    for seg in segments {
+        match seg {
+            CqSegment::Text(text) => res.push_str(&escape(text, false)),
+            CqSegment::Code { kind, params } => {
+                res.push_str("[CQ:");
+                res.push_str(kind);
+                for (k, v) in params {
+                    res.push(',');
+                    res.push_str(k);
+                    if let Some(v) = v {
+                        res.push('=');
+                        res.push_str(&escape(v, true));
+                    }
+                }
+                res.push(']');
+            }
+        }
+    }
+    res
+}
+
+fn code<const N: usize>(kind: &str, params: [(&str, String); N]) -> CqSegment {
+    CqSegment::Code {
+        kind: kind.to_owned(),
+        params: params
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), Some(v)))
+            .collect(),
+    }
+}
+
+fn get_timestamp(data: &PyDict, key: &str) -> PyResult<i64> {
+    let time: &PyAny = get(data, key)?;
+    match time.extract::<i64>() {
+        Ok(ts) => Ok(ts),
+        Err(_) => Ok(time.call_method0("timestamp")?.extract::<f64>()? as i64),
+    }
+}
+
+const MUSIC_KINDS: [(&str, &str); 5] = [
+    ("QQ", "qq"),
+    ("Netease", "163"),
+    ("Migu", "migu"),
+    ("Kugou", "kugou"),
+    ("Kuwo", "kuwo"),
+];
+
+/// 将单个元素字典转换为 CQ 码片段。
+pub fn element_to_segment(data: &PyDict) -> PyResult<CqSegment> {
+    let kind: String = get(data, "type")?;
+    Ok(match kind.as_str() {
+        "Text" => CqSegment::Text(get(data, "text")?),
+        "At" => {
+            let target: i64 = get(data, "target")?;
+            match data.get_item("display").map(|d| d.extract::<Option<String>>()) {
+                Some(Ok(Some(display))) => {
+                    code("at", [("qq", target.to_string()), ("name", display)])
+                }
+                _ => code("at", [("qq", target.to_string())]),
+            }
+        }
+        "AtAll" => code("at", [("qq", "all".into())]),
+        "Face" => code("face", [("id", get::<i32>(data, "index")?.to_string())]),
+        "SuperFace" => code(
+            "face",
+            [
+                ("id", get::<i32>(data, "index")?.to_string()),
+                ("type", "super".into()),
+                ("large", get::<bool>(data, "large")?.to_string()),
+            ],
+        ),
+        "Dice" => code("dice", [("result", get::<u8>(data, "value")?.to_string())]),
+        "FingerGuessing" => {
+            let choice: String = get(data, "choice")?;
+            code("rps", [("result", choice.to_lowercase())])
+        }
+        "Image" | "FlashImage" => {
+            let url: String = get(data, "url")?;
+            if kind == "FlashImage" {
+                code("image", [("file", url), ("type", "flash".into())])
+            } else {
+                code("image", [("file", url)])
+            }
+        }
+        "Audio" => code("record", [("file", get(data, "url")?)]),
+        "Reply" => code(
+            "reply",
+            [
+                ("id", get::<i32>(data, "seq")?.to_string()),
+                ("qq", get::<i64>(data, "sender")?.to_string()),
+                ("time", get_timestamp(data, "time")?.to_string()),
+                ("text", get(data, "content")?),
+            ],
+        ),
+        "LightApp" => code("json", [("data", get(data, "content")?)]),
+        "RichMessage" => code(
+            "xml",
+            [
+                ("data", get(data, "content")?),
+                ("resid", get::<i32>(data, "service_id")?.to_string()),
+            ],
+        ),
+        "MarketFace" => {
+            let face: SealedMarketFace = get(data, "raw")?;
+            let face = face.inner;
+            code(
+                "mface",
+                [
+                    ("id", hex::encode(&face.face_id)),
+                    ("tab_id", face.tab_id.to_string()),
+                    ("key", String::from_utf8_lossy(&face.encrypt_key).into_owned()),
+                    ("name", face.name),
+                    ("media_type", face.media_type.to_string()),
+                ],
+            )
+        }
+        "MusicShare" => {
+            let music_kind: String = get(data, "kind")?;
+            let music_kind = MUSIC_KINDS
+                .iter()
+                .find(|(name, _)| *name == music_kind)
+                .map(|(_, cq)| *cq)
+                .ok_or_else(|| PyValueError::new_err(format!("未知的音乐来源: {music_kind}")))?;
+            code(
+                "music",
+                [
+                    ("type", music_kind.into()),
+                    ("url", get(data, "jump_url")?),
+                    ("audio", get(data, "music_url")?),
+                    ("title", get(data, "title")?),
+                    ("content", get(data, "summary")?),
+                    ("image", get(data, "picture_url")?),
+                    ("brief", get(data, "brief")?),
+                ],
+            )
+        }
+        "Unknown" => {
+            let kind = match data.get_item("cq_type") {
+                Some(kind) => kind.extract()?,
+                None => String::from("unknown"),
+            };
+            // 由 CQ 码转换而来的元素将原始参数保存在 `params` 中，其他元素使用全部字段
+            let (fields, reserved) = match data.get_item("params") {
+                Some(params) if data.contains("cq_type")? => (params.downcast::<PyDict>()?, false),
+                _ => (data, true),
+            };
+            let mut params = Vec::new();
+            for (k, v) in fields.iter() {
+                let k: String = k.extract()?;
+                match k.as_str() {
+                    "type" | "cq_type" if reserved => {}
+                    _ if v.is_none() => params.push((k, None)),
+                    _ => params.push((k, Some(v.str()?.to_string()))),
+                }
+            }
+            CqSegment::Code { kind, params }
+        }
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "无法转换为 CQ 码的元素: {other}"
+            )))
+        }
+    })
+}
+
+fn param<'a>(params: &'a [(String, Option<String>)], key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == key)
+        .and_then(|(_, v)| v.as_deref())
+}
+
+fn parse_param<T: std::str::FromStr>(params: &[(String, Option<String>)], key: &str) -> Option<T> {
+    param(params, key).and_then(|v| v.parse().ok())
+}
+
+fn unknown_segment<'py>(
+    py: Python<'py>,
+    kind: &str,
+    params: &[(String, Option<String>)],
+) -> PyResult<&'py PyDict> {
+    let raw_params = PyDict::new(py);
+    for (k, v) in params {
+        raw_params.set_item(k, v)?;
+    }
+    Ok(dict! {py, type: "Unknown", cq_type: kind, params: raw_params})
+}
+
+/// 缺少必要参数时放弃转换，交由 `Unknown` 元素保存原始 CQ 码。
+macro_rules! require {
+    ($e:expr) => {
+        match $e {
+            Some(v) => v,
+            None => return Ok(None),
+        }
+    };
+}
+
+fn code_to_element<'py>(
+    py: Python<'py>,
+    kind: &str,
+    p: &[(String, Option<String>)],
+) -> PyResult<Option<&'py PyDict>> {
+    Ok(Some(match kind {
+        "at" => match require!(param(p, "qq")) {
+            "all" => dict! {py, type: "AtAll"},
+            qq => dict! {py,
+                type: "At",
+                target: require!(qq.parse::<i64>().ok()),
+                display: param(p, "name"),
+            },
+        },
+        "face" => {
+            let index: i32 = require!(parse_param(p, "id"));
+            let name = face::face_name(index);
+            if param(p, "type") == Some("super") {
+                let large = param(p, "large") != Some("false");
+                let sticker_type = match face::find_by_id(index).map(|f| f.kind) {
+                    Some(FaceKind::Animated(t)) if large => Some(t),
+                    _ => None,
+                };
+                dict! {py,
+                    type: "SuperFace",
+                    index: index,
+                    name: name,
+                    large: large,
+                    sticker_type: sticker_type,
+                }
+            } else {
+                dict! {py, type: "Face", index: index, name: name}
+            }
+        }
+        "dice" => dict! {py, type: "Dice", value: require!(parse_param::<u8>(p, "result"))},
+        "rps" => {
+            let choice = match param(p, "result") {
+                Some("rock") => "Rock",
+                Some("paper") => "Paper",
+                Some("scissors") => "Scissors",
+                _ => return Ok(None),
+            };
+            dict! {py, type: "FingerGuessing", choice: choice}
+        }
+        "image" => {
+            let url = require!(param(p, "url").or_else(|| param(p, "file")));
+            let kind = if param(p, "type") == Some("flash") {
+                "FlashImage"
+            } else {
+                "Image"
+            };
+            dict! {py, type: kind, url: url, raw: py.None()}
+        }
+        "record" => {
+            let url = require!(param(p, "url").or_else(|| param(p, "file")));
+            dict! {py, type: "Audio", url: url, raw: py.None()}
+        }
+        "reply" => dict! {py,
+            type: "Reply",
+            seq: require!(parse_param::<i32>(p, "id")),
+            sender: parse_param::<i64>(p, "qq").unwrap_or_default(),
+            time: datetime_from_ts(py, parse_param::<i64>(p, "time").unwrap_or_default())?,
+            content: param(p, "text").unwrap_or_default(),
+        },
+        "json" => dict! {py, type: "LightApp", content: require!(param(p, "data"))},
+        "xml" => dict! {py,
+            type: "RichMessage",
+            service_id: parse_param::<i32>(p, "resid").unwrap_or(1),
+            content: require!(param(p, "data")),
+        },
+        "mface" => {
+            let face = SealedMarketFace::build(
+                require!(param(p, "id")),
+                require!(parse_param(p, "tab_id")),
+                require!(param(p, "key")),
+                param(p, "name").unwrap_or_default().to_owned(),
+                parse_param(p, "media_type").unwrap_or_default(),
+                String::new(),
+            );
+            dict! {py, type: "MarketFace", raw: require!(face.ok()).into_py(py)}
+        }
+        "music" => {
+            let music_kind = require!(param(p, "type"));
+            let (music_kind, _) = require!(MUSIC_KINDS.iter().find(|(_, cq)| *cq == music_kind));
+            dict! {py,
+                type: "MusicShare",
+                kind: *music_kind,
+                title: require!(param(p, "title")),
+                summary: param(p, "content").unwrap_or_default(),
+                jump_url: require!(param(p, "url")),
+                picture_url: param(p, "image").unwrap_or_default(),
+                music_url: require!(param(p, "audio")),
+                brief: param(p, "brief").unwrap_or_default(),
+            }
+        }
+        _ => return Ok(None),
+    }))
+}
+
+/// 将 CQ 码片段转换为元素字典，无法识别或参数不完整的 CQ 码会被转换为 `Unknown` 元素。
+pub fn segment_to_element(py: Python, seg: CqSegment) -> PyResult<&PyDict> {
+    match seg {
+        CqSegment::Text(text) => Ok(dict! {py, type: "Text", text: text}),
+        CqSegment::Code { kind, params } => match code_to_element(py, &kind, &params)? {
+            Some(data) => Ok(data),
+            None => unknown_segment(py, &kind, &params),
+        },
+    }
+}
+
+#[pyfunction]
+pub fn elements_to_cq(elements: Vec<&PyDict>) -> PyResult<String> {
+    let segments = elements
+        .into_iter()
+        .map(element_to_segment)
+        .try_collect::<Vec<_>>()?;
+    Ok(render(&segments))
+}
+
+#[pyfunction]
+pub fn cq_to_elements(py: Python, text: &str) -> PyResult<Vec<PyObject>> {
+    parse(text)
+        .into_iter()
+        .map(|seg| segment_to_element(py, seg).map(|d| d.into_py(py)))
+        .try_collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_code_keeps_reserved_params() {
+        let text = "[CQ:music,type=custom,url=https://example.com/?a=1&amp;b=2,flag]";
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let segments = parse(text);
+            let elements = segments
+                .iter()
+                .cloned()
+                .map(|seg| segment_to_element(py, seg))
+                .try_collect::<Vec<_>>()
+                .unwrap();
+            let data = elements[0];
+            assert_eq!(get::<String>(data, "type").unwrap(), "Unknown");
+            assert_eq!(get::<String>(data, "cq_type").unwrap(), "music");
+            let back = elements
+                .into_iter()
+                .map(element_to_segment)
+                .try_collect::<Vec<_>>()
+                .unwrap();
+            assert_eq!(back, segments);
+            assert_eq!(render(&back), text);
+        });
+    }
+}
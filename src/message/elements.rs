@@ -1,7 +1,9 @@
 //! 消息元素。
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
+use pyo3::types::*;
+use pyo3_repr::PyRepr;
 use ricq::msg::elem::{FriendImage, GroupImage, MarketFace};
 
 use crate::props;
@@ -9,14 +11,14 @@ use crate::utils::py_bytes;
 
 #[pyfunction]
 pub fn face_name_from_id(id: i32) -> String {
-    ricq_core::msg::elem::Face::name(id).to_owned()
+    super::face::face_name(id)
 }
 
 #[pyfunction]
 pub fn face_id_from_name(name: &str) -> Option<i32> {
-    match ricq_core::msg::elem::Face::new_from_name(name) {
+    match super::face::find_by_name(name) {
         Some(f) => Some(f.index),
-        None => None,
+        None => ricq_core::msg::elem::Face::new_from_name(name).map(|f| f.index),
     }
 }
 
@@ -30,13 +32,131 @@ macro_rules! py_seal {
     };
 }
 
-py_seal!(SealedMarketFace => MarketFace);
+/// 商城表情的默认尺寸，与 RICQ 发送时使用的一致。
+pub const MARKET_FACE_SIZE: u32 = 200;
+
+#[pyclass]
+#[derive(PyRepr, Clone)]
+pub struct SealedMarketFace {
+    pub inner: MarketFace,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SealedMarketFace {
+    pub fn new(inner: MarketFace) -> Self {
+        Self {
+            inner,
+            width: MARKET_FACE_SIZE,
+            height: MARKET_FACE_SIZE,
+        }
+    }
+}
 
 #[pymethods]
 impl SealedMarketFace {
+    #[staticmethod]
+    #[pyo3(signature = (face_id, tab_id, key, name, *, media_type=0, magic_value=String::new()))]
+    pub fn build(
+        face_id: &str,
+        tab_id: i32,
+        key: &str,
+        name: String,
+        media_type: i32,
+        magic_value: String,
+    ) -> PyResult<Self> {
+        let face_id = hex::decode(face_id)
+            .map_err(|e| PyValueError::new_err(format!("无效的商城表情 ID: {e:?}")))?;
+        Ok(Self::new(MarketFace {
+            name,
+            face_id: face_id.into(),
+            tab_id,
+            item_type: 6,
+            sub_type: 3,
+            media_type,
+            encrypt_key: key.as_bytes().to_vec().into(),
+            magic_value,
+        }))
+    }
+
+    #[getter]
+    fn face_id(&self) -> String {
+        hex::encode(&self.inner.face_id)
+    }
+
     #[getter]
-    fn name(&self) -> String {
-        self.inner.name.clone()
+    fn key(&self) -> String {
+        String::from_utf8_lossy(&self.inner.encrypt_key).into_owned()
+    }
+}
+
+props!(self @ SealedMarketFace:
+    name => [String] self.inner.name.clone();
+    tab_id => [i32] self.inner.tab_id;
+    item_type => [i32] self.inner.item_type;
+    sub_type => [i32] self.inner.sub_type;
+    media_type => [i32] self.inner.media_type;
+    magic_value => [String] self.inner.magic_value.clone();
+    width => [u32] self.width;
+    height => [u32] self.height;
+);
+
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct MarketFaceItem {
+    pub face_id: String,
+    pub name: String,
+    pub tab_id: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct MarketFacePack {
+    pub tab_id: i32,
+    pub name: String,
+    pub faces: Py<PyTuple>, // PyTuple<MarketFaceItem>
+}
+
+/// 商城表情包的描述文件地址。
+pub fn market_face_pack_url(tab_id: i32) -> String {
+    format!(
+        "https://i.gtimg.cn/club/item/parcel/{}/{tab_id}_android.json",
+        tab_id % 10
+    )
+}
+
+impl MarketFacePack {
+    pub fn parse(py: Python, tab_id: i32, data: &[u8]) -> PyResult<Self> {
+        let json: serde_json::Value = serde_json::from_slice(data)
+            .map_err(|e| PyValueError::new_err(format!("无法解析商城表情包数据: {e:?}")))?;
+        let size = &json["supportSize"][0];
+        let width = size["Width"].as_u64().map_or(MARKET_FACE_SIZE, |v| v as u32);
+        let height = size["Height"].as_u64().map_or(MARKET_FACE_SIZE, |v| v as u32);
+        let faces = json["imgs"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|img| {
+                Some(
+                    MarketFaceItem {
+                        face_id: img["id"].as_str()?.to_owned(),
+                        name: img["name"].as_str().unwrap_or_default().to_owned(),
+                        tab_id,
+                        width,
+                        height,
+                    }
+                    .into_py(py),
+                )
+            })
+            .collect::<Vec<PyObject>>();
+        Ok(Self {
+            tab_id,
+            name: json["name"].as_str().unwrap_or_default().to_owned(),
+            faces: PyTuple::new(py, faces).into_py(py),
+        })
     }
 }
 
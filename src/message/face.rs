@@ -0,0 +1,580 @@
+//! QQ 表情目录与超级表情的打包。
+//!
+//! 普通表情使用 `Face` 消息元素发送，
+//! 新版小表情（索引 >= 260）使用 `CommonElem` 的 33 号服务，
+//! 大号动画表情（超级表情）使用 37 号服务。
+// LINK: https://github.com/Mrs4s/MiraiGo/blob/master/message/face.go
+
+use prost::Message as _;
+use pyo3::prelude::*;
+use pyo3::types::*;
+use pyo3_repr::PyRepr;
+use ricq_core::msg::MessageElem;
+use ricq_core::pb::msg::{CommonElem, Text as PbText};
+
+/// 大号超级表情发送时附带的兼容文本后缀。
+pub const SUPER_FACE_COMPAT_SUFFIX: &str = "请使用最新版手机QQ体验新功能";
+
+/// 新版小表情的最小索引。
+pub const SUPER_FACE_MIN_INDEX: i32 = 260;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaceKind {
+    /// 仅有小表情形式的表情
+    Normal,
+    /// 同时拥有大号动画形式的表情，附带贴纸类型
+    Animated(u32),
+}
+
+#[derive(Debug)]
+pub struct FaceEntry {
+    pub index: i32,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub kind: FaceKind,
+}
+
+impl FaceEntry {
+    /// 是否为新版小表情，需要使用 `CommonElem` 发送，仅由索引决定。
+    ///
+    /// 拥有大号动画形式的表情同样可能是新版小表情。
+    pub fn is_super(&self) -> bool {
+        self.index >= SUPER_FACE_MIN_INDEX
+    }
+}
+
+macro_rules! faces {
+    (@kind $index:literal) => {
+        FaceKind::Normal
+    };
+    (@kind $index:literal, anim) => {
+        FaceKind::Animated(1)
+    };
+    (@kind $index:literal, anim($sticker:literal)) => {
+        FaceKind::Animated($sticker)
+    };
+    ($($index:literal => $name:literal $([$($alias:literal),*])? $(@ $kind:ident $(($sticker:literal))?)?;)*) => {
+        &[$(
+            FaceEntry {
+                index: $index,
+                name: $name,
+                aliases: &[$($($alias),*)?],
+                kind: faces!(@kind $index $(, $kind $(($sticker))?)?),
+            },
+        )*]
+    };
+}
+
+pub static FACES: &[FaceEntry] = faces! {
+    0 => "惊讶" ["jy"];
+    1 => "撇嘴" ["pz"];
+    2 => "色" ["se"];
+    3 => "发呆" ["fd"];
+    4 => "得意" ["dy"];
+    5 => "流泪" ["ll"] @anim;
+    6 => "害羞" ["hx"];
+    7 => "闭嘴" ["bz"];
+    8 => "睡" ["shui"];
+    9 => "大哭" ["dk"];
+    10 => "尴尬" ["gg"];
+    11 => "发怒" ["fn"];
+    12 => "调皮" ["tp"];
+    13 => "呲牙" ["cy"];
+    14 => "微笑" ["wx"];
+    15 => "难过" ["ng"];
+    16 => "酷" ["kuk"];
+    18 => "抓狂" ["zk"];
+    19 => "吐" ["tu"];
+    20 => "偷笑" ["tx"];
+    21 => "可爱" ["ka"];
+    22 => "白眼" ["baiy"];
+    23 => "傲慢" ["am"];
+    24 => "饥饿" ["jie"];
+    25 => "困" ["kun"];
+    26 => "惊恐" ["jk"];
+    27 => "流汗" ["lh"];
+    28 => "憨笑" ["hanx"];
+    29 => "悠闲" ["yx"];
+    30 => "奋斗" ["fendou"];
+    31 => "咒骂" ["zhm"];
+    32 => "疑问" ["yiw"];
+    33 => "嘘" ["xu"];
+    34 => "晕" ["yun"];
+    35 => "折磨" ["zhem"];
+    36 => "衰" ["shuai"];
+    37 => "骷髅" ["kl"];
+    38 => "敲打" ["qiao"];
+    39 => "再见" ["zj"];
+    41 => "发抖" ["fad"];
+    42 => "爱情" ["aiq"];
+    43 => "跳跳" ["tiao"];
+    46 => "猪头" ["zt"];
+    49 => "拥抱" ["yb"];
+    53 => "蛋糕" ["dg"] @anim;
+    54 => "闪电" ["shd"];
+    55 => "炸弹" ["zhd"];
+    56 => "刀" ["dao"];
+    57 => "足球" ["zq"];
+    59 => "便便" ["bb"];
+    60 => "咖啡" ["kf"];
+    61 => "饭" ["fan"];
+    63 => "玫瑰" ["mg"];
+    64 => "凋谢" ["dx"];
+    66 => "爱心" ["xin"];
+    67 => "心碎" ["xs"];
+    69 => "礼物" ["lw"];
+    74 => "太阳" ["ty"] @anim;
+    75 => "月亮" ["yl"] @anim;
+    76 => "赞" ["qiang"];
+    77 => "踩" ["ruo"];
+    78 => "握手" ["ws"];
+    79 => "胜利" ["shl"];
+    85 => "飞吻" ["fw"];
+    86 => "怄火" ["oh"];
+    89 => "西瓜" ["xig"];
+    96 => "冷汗" ["lengh"];
+    97 => "擦汗" ["ch"];
+    98 => "抠鼻" ["kb"];
+    99 => "鼓掌" ["gz"];
+    100 => "糗大了" ["qd"];
+    101 => "坏笑" ["huaix"];
+    102 => "左哼哼" ["zhh"];
+    103 => "右哼哼" ["yhh"];
+    104 => "哈欠" ["hq"];
+    105 => "鄙视" ["bs"];
+    106 => "委屈" ["wq"];
+    107 => "快哭了" ["kk"];
+    108 => "阴险" ["yix"];
+    109 => "左亲亲" ["qq"];
+    110 => "吓" ["xia"];
+    111 => "可怜" ["kel"];
+    112 => "菜刀" ["cd"];
+    113 => "啤酒" ["pj"];
+    114 => "篮球" ["lq"] @anim;
+    115 => "乒乓" ["pp"];
+    116 => "示爱" ["sa"];
+    117 => "瓢虫" ["pc"];
+    118 => "抱拳" ["bq"];
+    119 => "勾引" ["gy"];
+    120 => "拳头" ["qt"];
+    121 => "差劲" ["cj"];
+    122 => "爱你" ["aini"];
+    123 => "NO" ["bu"];
+    124 => "OK" ["hd"];
+    125 => "转圈" ["zhq"];
+    126 => "磕头" ["kt"];
+    127 => "回头" ["ht"];
+    128 => "跳绳" ["tsh"];
+    129 => "挥手" ["hsh"];
+    130 => "激动" ["jd"];
+    131 => "街舞" ["jw"];
+    132 => "献吻" ["xw"];
+    133 => "左太极" ["zuotj"];
+    134 => "右太极" ["youtj"];
+    136 => "双喜" ["shx"];
+    137 => "鞭炮" ["bp"] @anim;
+    138 => "灯笼" ["dl"];
+    140 => "K歌" ["kg"];
+    144 => "喝彩" ["hc"];
+    145 => "祈祷" ["qidao"];
+    146 => "爆筋" ["bj"];
+    147 => "棒棒糖" ["bbt"];
+    148 => "喝奶" ["hn"];
+    151 => "飞机" ["fj"];
+    158 => "钞票" ["chp"];
+    168 => "药" ["yao"];
+    169 => "手枪" ["shq"];
+    171 => "茶" ["cha"];
+    172 => "眨眼睛" ["zyj"];
+    173 => "泪奔" ["lb"];
+    174 => "无奈" ["wn"];
+    175 => "卖萌" ["mm"];
+    176 => "小纠结" ["xjj"];
+    177 => "喷血" ["px"];
+    178 => "斜眼笑" ["xyx"];
+    179 => "doge" ["dog"];
+    180 => "惊喜" ["jxi"];
+    181 => "戳一戳" ["骚扰", "cyc"] @anim;
+    182 => "笑哭" ["xk"];
+    183 => "我最美" ["wzm"];
+    184 => "河蟹";
+    185 => "羊驼" ["yt"];
+    187 => "幽灵" ["youl"];
+    188 => "蛋" ["dan"];
+    190 => "菊花" ["jh"];
+    192 => "红包" ["hb"];
+    193 => "大笑";
+    194 => "不开心" ["bkx"];
+    197 => "冷漠" ["lm"];
+    198 => "呃" ["e"];
+    199 => "好棒" ["hbang"];
+    200 => "拜托" ["bt"];
+    201 => "点赞" ["dz"];
+    202 => "无聊" ["wl"];
+    203 => "托脸" ["tl"];
+    204 => "吃" ["chi"];
+    205 => "送花" ["sh"];
+    206 => "害怕" ["hp"];
+    207 => "花痴";
+    208 => "小样儿" ["xyr"];
+    210 => "飙泪" ["bl"];
+    211 => "我不看" ["wbk"];
+    212 => "托腮" ["ts"];
+    214 => "啵啵" ["bobo"];
+    215 => "糊脸" ["hl"];
+    216 => "拍头" ["pt"];
+    217 => "扯一扯";
+    218 => "舔一舔" ["tyt"];
+    219 => "蹭一蹭";
+    220 => "拽炸天" ["zzt"];
+    221 => "顶呱呱" ["dgg"];
+    222 => "抱抱" ["baob"];
+    223 => "暴击" ["baoj"];
+    224 => "开枪" ["kq"];
+    225 => "撩一撩" ["lyl"];
+    226 => "拍桌";
+    227 => "拍手" ["ps"];
+    228 => "恭喜" ["gx"];
+    229 => "干杯" ["gb"];
+    230 => "嘲讽" ["cf"];
+    231 => "哼" ["heng"];
+    232 => "佛系" ["fx"];
+    233 => "掐一掐" ["qyq"];
+    234 => "惊呆" ["jdai"];
+    235 => "颤抖" ["chd"];
+    236 => "啃头";
+    237 => "偷看" ["tk"];
+    238 => "扇脸";
+    239 => "原谅";
+    240 => "喷脸" ["pl"];
+    241 => "生日快乐" ["srkl"];
+    242 => "头撞击" ["tzj"];
+    243 => "甩头" ["st"];
+    244 => "扔狗" ["rg"];
+    245 => "加油必胜" ["jybs"];
+    246 => "加油抱抱" ["jybb"];
+    247 => "口罩护体" ["kzht"];
+    260 => "搬砖中";
+    261 => "忙到飞起";
+    262 => "脑阔疼";
+    263 => "沧桑";
+    264 => "捂脸";
+    265 => "辣眼睛";
+    266 => "哦哟";
+    267 => "头秃";
+    268 => "问号脸";
+    269 => "暗中观察";
+    270 => "emm";
+    271 => "吃瓜";
+    272 => "呵呵哒";
+    273 => "我酸了";
+    274 => "太南了";
+    276 => "辣椒酱";
+    277 => "汪汪";
+    278 => "汗";
+    279 => "打脸";
+    280 => "击掌";
+    281 => "无眼笑";
+    282 => "敬礼";
+    283 => "狂笑";
+    284 => "面无表情";
+    285 => "摸鱼";
+    286 => "魔鬼笑";
+    287 => "哦";
+    288 => "请";
+    289 => "睁眼";
+    290 => "敲开心";
+    291 => "震惊";
+    292 => "让我康康";
+    293 => "摸锦鲤";
+    294 => "期待";
+    295 => "拿到红包";
+    296 => "真好";
+    297 => "拜谢";
+    298 => "元宝";
+    299 => "牛啊";
+    300 => "胖三斤";
+    301 => "好闪";
+    302 => "左拜年";
+    303 => "右拜年";
+    304 => "红包包";
+    305 => "右亲亲";
+    306 => "牛气冲天";
+    307 => "喵喵";
+    308 => "求红包";
+    309 => "谢红包";
+    310 => "新年烟花";
+    311 => "打call" @anim;
+    312 => "变形" @anim;
+    313 => "嗑到了";
+    314 => "仔细分析" @anim;
+    315 => "加油";
+    316 => "我没事";
+    317 => "菜汪" @anim;
+    318 => "崇拜" @anim;
+    319 => "比心" @anim;
+    320 => "庆祝" @anim;
+    321 => "老色痞";
+    322 => "拒绝";
+    323 => "嫌弃";
+    324 => "吃糖" @anim;
+    325 => "惊吓" @anim;
+    326 => "生气" @anim;
+    327 => "加一";
+    328 => "错号";
+    329 => "对号";
+    330 => "完成";
+    331 => "明白";
+    332 => "举牌牌";
+    333 => "烟花" @anim;
+    334 => "虎虎生威";
+    336 => "豹富";
+    337 => "花朵脸" @anim;
+    338 => "我想开了" @anim;
+    339 => "舔屏" @anim;
+    340 => "热化了";
+    341 => "打招呼" @anim;
+    342 => "酸Q" @anim;
+    343 => "我方了" @anim;
+    344 => "大怨种" @anim;
+    345 => "红包多多" @anim;
+    346 => "你真棒棒" @anim;
+    347 => "大展宏兔";
+    348 => "福萝卜";
+    349 => "坚强" @anim;
+    350 => "贴贴" @anim;
+    351 => "敲敲" @anim;
+    352 => "咦";
+    353 => "拜托";
+    354 => "尊嘟假嘟";
+    355 => "耶";
+    356 => "666";
+    357 => "裂开";
+    358 => "骰子" @anim(2);
+    359 => "包剪锤" @anim(2);
+};
+
+fn normalize(name: &str) -> &str {
+    name.strip_prefix('/').unwrap_or(name)
+}
+
+pub fn find_by_id(index: i32) -> Option<&'static FaceEntry> {
+    FACES.iter().find(|f| f.index == index)
+}
+
+/// 按名称或别名查找表情，支持 `/微笑`、`/wx` 形式。
+pub fn find_by_name(name: &str) -> Option<&'static FaceEntry> {
+    let name = normalize(name);
+    FACES
+        .iter()
+        .find(|f| f.name == name)
+        .or_else(|| FACES.iter().find(|f| f.aliases.contains(&name)))
+}
+
+/// 获取表情名称，目录内未收录时回退至 RICQ 的表情表。
+pub fn face_name(index: i32) -> String {
+    match find_by_id(index) {
+        Some(entry) => entry.name.to_owned(),
+        None => ricq_core::msg::elem::Face::name(index).to_owned(),
+    }
+}
+
+/// 将目录中的新版小表情打包为 `CommonElem`，其他表情返回 `None`。
+pub fn pack_small_face(index: i32) -> Option<Vec<MessageElem>> {
+    let entry = find_by_id(index).filter(|entry| entry.is_super())?;
+    Some(
+        SuperFace {
+            index,
+            name: entry.name.to_owned(),
+            large: false,
+            sticker_type: None,
+        }
+        .pack(),
+    )
+}
+
+/// 新版小表情，对应 `MsgElemInfo_servtype33`。
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SmallFaceElem {
+    #[prost(uint32, optional, tag = "1")]
+    pub index: Option<u32>,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub text: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub compat: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "4")]
+    pub buf: Option<Vec<u8>>,
+}
+
+/// 大号超级表情，对应 `MsgElemInfo_servtype37`。
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AnimatedFaceElem {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub pack_id: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub sticker_id: Option<Vec<u8>>,
+    #[prost(uint32, optional, tag = "3")]
+    pub qsid: Option<u32>,
+    #[prost(uint32, optional, tag = "4")]
+    pub source_type: Option<u32>,
+    #[prost(uint32, optional, tag = "5")]
+    pub sticker_type: Option<u32>,
+    #[prost(bytes = "vec", optional, tag = "6")]
+    pub result_id: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub text: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "8")]
+    pub surprise_id: Option<Vec<u8>>,
+    #[prost(uint32, optional, tag = "9")]
+    pub random_type: Option<u32>,
+}
+
+/// 从 `CommonElem` 中解出的超级表情。
+#[derive(Debug, Clone)]
+pub struct SuperFace {
+    pub index: i32,
+    pub name: String,
+    pub large: bool,
+    pub sticker_type: Option<u32>,
+}
+
+impl SuperFace {
+    pub fn decode(elem: &CommonElem) -> Option<Self> {
+        let strip_name = |text: Option<Vec<u8>>| {
+            text.map(|t| normalize(&String::from_utf8_lossy(&t)).to_owned())
+        };
+        match elem.service_type() {
+            33 => {
+                let face = SmallFaceElem::decode(elem.pb_elem()).ok()?;
+                let index = face.index? as i32;
+                Some(Self {
+                    index,
+                    name: strip_name(face.text).unwrap_or_else(|| face_name(index)),
+                    large: false,
+                    sticker_type: None,
+                })
+            }
+            37 => {
+                let face = AnimatedFaceElem::decode(elem.pb_elem()).ok()?;
+                let index = face.qsid? as i32;
+                Some(Self {
+                    index,
+                    name: strip_name(face.text).unwrap_or_else(|| face_name(index)),
+                    large: true,
+                    sticker_type: face.sticker_type,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn pack(self) -> Vec<MessageElem> {
+        let text = format!("/{}", self.name).into_bytes();
+        if !self.large {
+            let pb_elem = SmallFaceElem {
+                index: Some(self.index as u32),
+                text: Some(text.clone()),
+                compat: Some(text),
+                buf: None,
+            };
+            return vec![MessageElem::CommonElem(CommonElem {
+                service_type: Some(33),
+                pb_elem: Some(pb_elem.encode_to_vec().into()),
+                business_type: Some(1),
+            })];
+        }
+        let pb_elem = AnimatedFaceElem {
+            pack_id: Some(b"1".to_vec()),
+            sticker_id: Some(self.index.to_string().into_bytes()),
+            qsid: Some(self.index as u32),
+            source_type: Some(1),
+            sticker_type: Some(self.sticker_type.unwrap_or(1)),
+            text: Some(text),
+            random_type: Some(1),
+            ..Default::default()
+        };
+        vec![
+            MessageElem::CommonElem(CommonElem {
+                service_type: Some(37),
+                pb_elem: Some(pb_elem.encode_to_vec().into()),
+                business_type: Some(1),
+            }),
+            MessageElem::Text(PbText {
+                str: Some(format!("[{}]{SUPER_FACE_COMPAT_SUFFIX}", self.name)),
+                ..Default::default()
+            }),
+        ]
+    }
+}
+
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct FaceInfo {
+    pub index: i32,
+    pub name: String,
+    pub aliases: Py<PyTuple>, // PyTuple<str>
+    pub is_super: bool,
+    pub is_animated: bool,
+    pub sticker_type: Option<u32>,
+}
+
+impl FaceInfo {
+    fn new(py: Python, entry: &FaceEntry) -> Self {
+        let (is_animated, sticker_type) = match entry.kind {
+            FaceKind::Normal => (false, None),
+            FaceKind::Animated(sticker_type) => (true, Some(sticker_type)),
+        };
+        Self {
+            index: entry.index,
+            name: entry.name.to_owned(),
+            aliases: PyTuple::new(py, entry.aliases).into_py(py),
+            is_super: entry.is_super(),
+            is_animated,
+            sticker_type,
+        }
+    }
+}
+
+#[pyfunction]
+pub fn face_catalogue(py: Python) -> Vec<FaceInfo> {
+    FACES.iter().map(|entry| FaceInfo::new(py, entry)).collect()
+}
+
+#[pyfunction]
+pub fn face_info(py: Python, index: i32) -> Option<FaceInfo> {
+    find_by_id(index).map(|entry| FaceInfo::new(py, entry))
+}
+
+#[pyfunction]
+pub fn face_info_from_name(py: Python, name: &str) -> Option<FaceInfo> {
+    find_by_name(name).map(|entry| FaceInfo::new(py, entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animated_new_faces_are_super() {
+        let entry = find_by_id(314).unwrap();
+        assert_eq!(entry.kind, FaceKind::Animated(1));
+        assert!(entry.is_super());
+        assert!(!find_by_id(5).unwrap().is_super());
+    }
+
+    #[test]
+    fn new_faces_send_as_super_faces() {
+        for index in [334, 314] {
+            let elems = pack_small_face(index).unwrap();
+            let [MessageElem::CommonElem(elem)] = elems.as_slice() else {
+                panic!("{index} 未打包为单个 CommonElem");
+            };
+            assert_eq!(elem.service_type(), 33);
+            let face = SuperFace::decode(elem).unwrap();
+            assert_eq!(face.index, index);
+            assert!(!face.large);
+        }
+        assert_eq!(find_by_id(334).unwrap().kind, FaceKind::Normal);
+        assert!(pack_small_face(5).is_none());
+    }
+}
@@ -1,2 +1,7 @@
 pub mod convert;
+pub mod cq;
 pub mod elements;
+pub mod face;
+pub mod qr;
+pub mod transcript;
+pub mod wire;
@@ -0,0 +1,134 @@
+//! 二维码的识别与生成。
+
+use std::io::Cursor;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::*;
+use pyo3_repr::PyRepr;
+
+#[pyclass(get_all, module = "ichika.core")]
+#[derive(PyRepr, Clone)]
+pub struct DecodedQRCode {
+    pub content: String,
+    pub bounds: Py<PyTuple>, // PyTuple<(i32, i32)>
+    pub version: usize,
+    pub ecc_level: u16,
+}
+
+/// 识别图像中的所有二维码，返回 `(内容, 四角坐标, 版本, 纠错等级)`。
+///
+/// 无法解码的二维码会被跳过，发现了二维码但全部无法解码时返回解码错误。
+pub fn decode_grids(data: &[u8]) -> PyResult<Vec<(String, [(i32, i32); 4], usize, u16)>> {
+    let image = image::load_from_memory(data)
+        .map_err(|e| PyValueError::new_err(format!("加载二维码图像出现错误: {e:?}")))?
+        .to_luma8();
+    let mut image = rqrr::PreparedImage::prepare(image);
+    let mut decoded = vec![];
+    let mut last_err = None;
+    for grid in image.detect_grids() {
+        match grid.decode() {
+            Ok((meta, content)) => {
+                let bounds = grid.bounds.map(|p| (p.x, p.y));
+                decoded.push((content, bounds, meta.version.0, meta.ecc_level));
+            }
+            Err(e) => {
+                tracing::warn!("解码二维码出现错误: {:?}", e);
+                last_err = Some(e);
+            }
+        }
+    }
+    if decoded.is_empty() && let Some(e) = last_err {
+        return Err(PyValueError::new_err(format!("解码二维码出现错误: {e:?}")));
+    }
+    Ok(decoded)
+}
+
+/// 将文本编码为二维码的模块矩阵，`true` 为浅色。
+pub fn encode_modules(content: &[u8], ecc: qrcode::EcLevel) -> PyResult<Vec<Vec<bool>>> {
+    let qrcode = qrcode::QrCode::with_error_correction_level(content, ecc)
+        .map_err(|e| PyValueError::new_err(format!("生成二维码数据出现错误: {e:?}")))?;
+    let width = qrcode.width();
+    Ok(qrcode
+        .into_colors()
+        .chunks(width)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|c| match c {
+                    qrcode::Color::Light => true,
+                    qrcode::Color::Dark => false,
+                })
+                .collect()
+        })
+        .collect())
+}
+
+fn parse_ecc_level(ecc: &str) -> PyResult<qrcode::EcLevel> {
+    Ok(match ecc {
+        "L" => qrcode::EcLevel::L,
+        "M" => qrcode::EcLevel::M,
+        "Q" => qrcode::EcLevel::Q,
+        "H" => qrcode::EcLevel::H,
+        level => {
+            return Err(PyValueError::new_err(format!(
+                "无法识别的纠错等级: {level}"
+            )))
+        }
+    })
+}
+
+/// 生成的二维码图像的最大边长。
+const MAX_IMAGE_SIZE: u32 = 8192;
+
+fn render_png(modules: &[Vec<bool>], scale: u32) -> PyResult<Vec<u8>> {
+    const QUIET_ZONE: u32 = 4;
+    if scale == 0 {
+        return Err(PyValueError::new_err("二维码缩放倍数必须大于 0"));
+    }
+    let width = modules.len() as u32;
+    let size = (width + QUIET_ZONE * 2)
+        .checked_mul(scale)
+        .filter(|size| *size <= MAX_IMAGE_SIZE)
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "二维码缩放倍数过大, 图像边长不能超过 {MAX_IMAGE_SIZE} 像素"
+            ))
+        })?;
+    let image = image::GrayImage::from_fn(size, size, |x, y| {
+        let (mx, my) = (x / scale, y / scale);
+        let light = mx < QUIET_ZONE
+            || my < QUIET_ZONE
+            || mx >= width + QUIET_ZONE
+            || my >= width + QUIET_ZONE
+            || modules[(my - QUIET_ZONE) as usize][(mx - QUIET_ZONE) as usize];
+        image::Luma([if light { 255 } else { 0 }])
+    });
+    let mut buf = Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut buf, image::ImageOutputFormat::Png)
+        .map_err(|e| PyValueError::new_err(format!("编码二维码图像出现错误: {e:?}")))?;
+    Ok(buf.into_inner())
+}
+
+#[pyfunction]
+pub fn decode_qrcodes(py: Python, data: &[u8]) -> PyResult<Vec<DecodedQRCode>> {
+    let grids = py.allow_threads(|| decode_grids(data))?;
+    Ok(grids
+        .into_iter()
+        .map(|(content, bounds, version, ecc_level)| DecodedQRCode {
+            content,
+            bounds: PyTuple::new(py, bounds.map(|p| p.to_object(py))).into_py(py),
+            version,
+            ecc_level,
+        })
+        .collect())
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, scale=8, ecc="M"))]
+pub fn make_qrcode_image(py: Python, text: &str, scale: u32, ecc: &str) -> PyResult<Py<PyBytes>> {
+    let ecc = parse_ecc_level(ecc)?;
+    let png = py.allow_threads(|| render_png(&encode_modules(text.as_bytes(), ecc)?, scale))?;
+    Ok(PyBytes::new(py, &png).into_py(py))
+}
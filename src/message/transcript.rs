@@ -0,0 +1,688 @@
+//! 聊天记录的导出与导入。
+//!
+//! 转发消息树会先被转换为 [`TranscriptNode`]，再渲染为 HTML、Markdown、纯文本或 JSON。
+//! JSON 格式可以被重新读取为转发消息。
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use base64::Engine as _;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::*;
+use ricq::msg::elem::{FlashImage, RQElem};
+use ricq::msg::MessageChain;
+use ricq::structs::ForwardMessage;
+use serde::{Deserialize, Serialize};
+
+use super::convert::{deserialize_message_chain, py_deserialize, py_serialize};
+use crate::client::params::PyForwardMessage;
+use crate::dict;
+use crate::events::model::timestamp;
+use crate::utils::datetime_from_ts;
+
+/// 聊天记录 JSON 格式的版本。
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Segment {
+    Text { text: String },
+    At { target: i64, display: String },
+    AtAll,
+    Face { index: i32, name: String },
+    Image { url: String },
+    Reply { seq: i32, sender: i64, time: i32, content: String },
+    Other { text: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptNode {
+    pub sender_id: i64,
+    pub sender_name: String,
+    pub time: i32,
+    #[serde(flatten)]
+    pub body: TranscriptBody,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptBody {
+    Content(Vec<Segment>),
+    Forward(Vec<TranscriptNode>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub title: String,
+    pub messages: Vec<TranscriptNode>,
+}
+
+fn image_url(elem: &RQElem) -> Option<String> {
+    Some(match elem {
+        RQElem::GroupImage(i) => i.url(),
+        RQElem::FriendImage(i) => i.url(),
+        RQElem::FlashImage(FlashImage::GroupImage(i)) => i.url(),
+        RQElem::FlashImage(FlashImage::FriendImage(i)) => i.url(),
+        _ => return None,
+    })
+}
+
+fn segments(chain: MessageChain) -> Vec<Segment> {
+    use ricq_core::msg::MessageElem as BaseElem;
+    let mut res = Vec::new();
+    for e in chain.0 {
+        let elem = match e {
+            BaseElem::SrcMsg(reply) => {
+                let reply: ricq::msg::elem::Reply = reply.into();
+                res.push(Segment::Reply {
+                    seq: reply.reply_seq,
+                    sender: reply.sender,
+                    time: reply.time,
+                    content: reply.elements.to_string(),
+                });
+                continue;
+            }
+            elem => RQElem::from(elem),
+        };
+        if let Some(url) = image_url(&elem) {
+            res.push(Segment::Image { url });
+            continue;
+        }
+        res.push(match elem {
+            RQElem::Text(t) => Segment::Text { text: t.content },
+            RQElem::At(a) if a.target == 0 => Segment::AtAll,
+            RQElem::At(a) => Segment::At {
+                target: a.target,
+                display: a.display,
+            },
+            RQElem::Face(f) => Segment::Face {
+                index: f.index,
+                name: f.name,
+            },
+            RQElem::Other(_) => continue,
+            other => Segment::Other {
+                text: other.to_string(),
+            },
+        });
+    }
+    res
+}
+
+impl From<ForwardMessage> for TranscriptNode {
+    fn from(value: ForwardMessage) -> Self {
+        match value {
+            ForwardMessage::Message(msg) => Self {
+                sender_id: msg.sender_id,
+                sender_name: msg.sender_name,
+                time: msg.time,
+                body: TranscriptBody::Content(segments(msg.elements)),
+            },
+            ForwardMessage::Forward(fwd) => Self {
+                sender_id: fwd.sender_id,
+                sender_name: fwd.sender_name,
+                time: fwd.time,
+                body: TranscriptBody::Forward(fwd.nodes.into_iter().map(Self::from).collect()),
+            },
+        }
+    }
+}
+
+/// `render_transcript` 接受的单条消息。
+pub enum TranscriptItem {
+    /// 与 `upload_forward_msg` 相同格式的转发消息字典
+    Forward(PyForwardMessage),
+    /// 消息事件、`CachedMessage` 或 `(sender, time, MessageChain)` 三元组
+    Message {
+        sender_id: i64,
+        sender_name: String,
+        time: i32,
+        content: Py<PyList>,
+    },
+}
+
+fn chain_list(content: &PyAny) -> PyResult<Py<PyList>> {
+    let list: &PyList = match content.downcast::<PyList>() {
+        Ok(list) => list,
+        Err(_) => py_serialize(content.py()).call1((content,))?.downcast()?,
+    };
+    Ok(list.into())
+}
+
+impl<'s> FromPyObject<'s> for TranscriptItem {
+    fn extract(ob: &'s PyAny) -> PyResult<Self> {
+        if ob.is_instance_of::<PyDict>() {
+            return Ok(Self::Forward(ob.extract()?));
+        }
+        if let Ok(tuple) = ob.downcast::<PyTuple>() {
+            let (sender, time, content): (&PyAny, &PyAny, &PyAny) = tuple.extract()?;
+            let sender_id: i64 = sender.extract()?;
+            return Ok(Self::Message {
+                sender_id,
+                sender_name: sender_id.to_string(),
+                time: timestamp(time)? as i32,
+                content: chain_list(content)?,
+            });
+        }
+        let sender_id: i64 = ob.getattr("sender")?.extract()?;
+        let sender_name = match ob.getattr("sender_info") {
+            Ok(info) => info.getattr("name")?.extract()?,
+            Err(_) => sender_id.to_string(),
+        };
+        Ok(Self::Message {
+            sender_id,
+            sender_name,
+            time: timestamp(ob.getattr("source")?.getattr("time")?)? as i32,
+            content: chain_list(ob.getattr("content")?)?,
+        })
+    }
+}
+
+impl TranscriptItem {
+    fn into_node(self, py: Python) -> PyResult<TranscriptNode> {
+        match self {
+            Self::Forward(msg) => Ok(ForwardMessage::try_from(msg)?.into()),
+            Self::Message {
+                sender_id,
+                sender_name,
+                time,
+                content,
+            } => Ok(TranscriptNode {
+                sender_id,
+                sender_name,
+                time,
+                body: TranscriptBody::Content(segments(deserialize_message_chain(
+                    content.as_ref(py),
+                )?)),
+            }),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            '\n' => res.push_str("<br>"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+        ) {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// 对 Markdown 链接目标中会提前结束链接的字符进行百分号编码。
+fn escape_markdown_url(url: &str) -> String {
+    let mut res = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_whitespace() || c.is_control() || matches!(c, '(' | ')' | '<' | '>' | '\\') {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                let _ = write!(res, "%{b:02X}");
+            }
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+fn image_mime(data: &[u8]) -> &'static str {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, ..] => "image/jpeg",
+        [b'G', b'I', b'F', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'B', b'M', ..] => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Html,
+    Markdown,
+    Text,
+    Json,
+}
+
+impl TryFrom<&str> for TranscriptFormat {
+    type Error = PyErr;
+
+    fn try_from(value: &str) -> PyResult<Self> {
+        Ok(match value {
+            "html" => Self::Html,
+            "markdown" => Self::Markdown,
+            "text" => Self::Text,
+            "json" => Self::Json,
+            format => {
+                return Err(PyValueError::new_err(format!(
+                    "无法识别的聊天记录格式: {format}"
+                )))
+            }
+        })
+    }
+}
+
+struct Renderer<'a> {
+    py: Python<'a>,
+    images: &'a HashMap<String, Vec<u8>>,
+    out: String,
+}
+
+impl<'a> Renderer<'a> {
+    fn time(&self, time: i32) -> PyResult<String> {
+        datetime_from_ts(self.py, time)?
+            .call_method1("strftime", ("%Y-%m-%d %H:%M:%S",))?
+            .extract()
+    }
+
+    fn image_src(&self, url: &str) -> String {
+        match self.images.get(url) {
+            Some(data) => format!(
+                "data:{};base64,{}",
+                image_mime(data),
+                base64::engine::general_purpose::STANDARD.encode(data)
+            ),
+            None => url.to_owned(),
+        }
+    }
+
+    fn html(&mut self, nodes: &[TranscriptNode]) -> PyResult<()> {
+        for node in nodes {
+            let time = self.time(node.time)?;
+            let _ = write!(
+                self.out,
+                r#"<div class="message"><div class="meta"><span class="sender">{}</span> <span class="uin">({})</span> <time>{}</time></div>"#,
+                escape_html(&node.sender_name),
+                node.sender_id,
+                time
+            );
+            match &node.body {
+                TranscriptBody::Content(segments) => {
+                    self.out.push_str(r#"<div class="content">"#);
+                    for seg in segments {
+                        self.html_segment(seg)?;
+                    }
+                    self.out.push_str("</div>");
+                }
+                TranscriptBody::Forward(nodes) => {
+                    self.out
+                        .push_str(r#"<details class="forward" open><summary>聊天记录</summary>"#);
+                    self.html(nodes)?;
+                    self.out.push_str("</details>");
+                }
+            }
+            self.out.push_str("</div>\n");
+        }
+        Ok(())
+    }
+
+    fn html_segment(&mut self, seg: &Segment) -> PyResult<()> {
+        match seg {
+            Segment::Text { text } | Segment::Other { text } => {
+                self.out.push_str(&escape_html(text));
+            }
+            Segment::At { target, display } => {
+                let _ = write!(
+                    self.out,
+                    r#"<span class="at" title="{target}">{}</span>"#,
+                    escape_html(display)
+                );
+            }
+            Segment::AtAll => self.out.push_str(r#"<span class="at">@全体成员</span>"#),
+            Segment::Face { name, .. } => {
+                let _ = write!(self.out, r#"<span class="face">[{}]</span>"#, escape_html(name));
+            }
+            Segment::Image { url } => {
+                let _ = write!(
+                    self.out,
+                    r#"<img src="{}" alt="[图片]" loading="lazy">"#,
+                    escape_html(&self.image_src(url))
+                );
+            }
+            Segment::Reply {
+                sender,
+                time,
+                content,
+                ..
+            } => {
+                let time = self.time(*time)?;
+                let _ = write!(
+                    self.out,
+                    r#"<blockquote class="reply"><div class="meta">{sender} <time>{time}</time></div>{}</blockquote>"#,
+                    escape_html(content)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn markdown(&mut self, nodes: &[TranscriptNode], depth: usize) -> PyResult<()> {
+        let prefix = "> ".repeat(depth);
+        for node in nodes {
+            let time = self.time(node.time)?;
+            let _ = writeln!(
+                self.out,
+                "{prefix}**{}** ({}) · {time}\n{prefix}",
+                escape_markdown(&node.sender_name),
+                node.sender_id
+            );
+            match &node.body {
+                TranscriptBody::Content(segments) => {
+                    let mut line = String::new();
+                    for seg in segments {
+                        match seg {
+                            Segment::Text { text } | Segment::Other { text } => {
+                                line.push_str(&escape_markdown(text))
+                            }
+                            Segment::At { display, .. } => line.push_str(&escape_markdown(display)),
+                            Segment::AtAll => line.push_str("@全体成员"),
+                            Segment::Face { name, .. } => {
+                                let _ = write!(line, "\\[{}\\]", escape_markdown(name));
+                            }
+                            Segment::Image { url } => {
+                                let _ = write!(
+                                    line,
+                                    "![图片]({})",
+                                    escape_markdown_url(&self.image_src(url))
+                                );
+                            }
+                            Segment::Reply {
+                                sender,
+                                time,
+                                content,
+                                ..
+                            } => {
+                                let time = self.time(*time)?;
+                                let _ = writeln!(
+                                    self.out,
+                                    "{prefix}> 回复 {sender} · {time}: {}\n{prefix}",
+                                    escape_markdown(content).replace('\n', " ")
+                                );
+                            }
+                        }
+                    }
+                    for l in line.split('\n') {
+                        let _ = writeln!(self.out, "{prefix}{l}  ");
+                    }
+                }
+                TranscriptBody::Forward(nodes) => {
+                    self.markdown(nodes, depth + 1)?;
+                }
+            }
+            let _ = writeln!(self.out, "{prefix}");
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, nodes: &[TranscriptNode], depth: usize) -> PyResult<()> {
+        let indent = "    ".repeat(depth);
+        for node in nodes {
+            let time = self.time(node.time)?;
+            let _ = write!(
+                self.out,
+                "{indent}[{time}] {}({}): ",
+                node.sender_name, node.sender_id
+            );
+            match &node.body {
+                TranscriptBody::Content(segments) => {
+                    for seg in segments {
+                        match seg {
+                            Segment::Text { text } | Segment::Other { text } => {
+                                self.out.push_str(&text.replace('\n', &format!("\n{indent}")))
+                            }
+                            Segment::At { display, .. } => self.out.push_str(display),
+                            Segment::AtAll => self.out.push_str("@全体成员"),
+                            Segment::Face { name, .. } => {
+                                let _ = write!(self.out, "[{name}]");
+                            }
+                            Segment::Image { url } => {
+                                let _ = write!(self.out, "[图片: {url}]");
+                            }
+                            Segment::Reply {
+                                sender, content, ..
+                            } => {
+                                let _ = write!(
+                                    self.out,
+                                    "[回复 {sender}: {}] ",
+                                    content.replace('\n', " ")
+                                );
+                            }
+                        }
+                    }
+                    self.out.push('\n');
+                }
+                TranscriptBody::Forward(nodes) => {
+                    self.out.push_str("[聊天记录]\n");
+                    self.text(nodes, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:860px;margin:auto;padding:1em;background:#f5f5f5}\
+.message{background:#fff;border-radius:6px;padding:.6em .8em;margin:.5em 0}\
+.meta{color:#888;font-size:.85em;margin-bottom:.3em}.sender{color:#333;font-weight:bold}\
+.content img{max-width:100%;display:block;margin:.3em 0}.at{color:#1e88e5}\
+.reply{border-left:3px solid #ccc;margin:0 0 .4em;padding:.2em .6em;color:#666}\
+.forward{border-left:3px solid #90caf9;padding-left:.6em}";
+
+pub fn render(
+    py: Python,
+    nodes: &[TranscriptNode],
+    format: TranscriptFormat,
+    title: &str,
+    images: &HashMap<String, Vec<u8>>,
+) -> PyResult<String> {
+    let mut renderer = Renderer {
+        py,
+        images,
+        out: String::new(),
+    };
+    match format {
+        TranscriptFormat::Html => {
+            let _ = write!(
+                renderer.out,
+                "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title><style>{HTML_STYLE}</style></head><body><h1>{0}</h1>\n",
+                escape_html(title)
+            );
+            renderer.html(nodes)?;
+            renderer.out.push_str("</body></html>\n");
+        }
+        TranscriptFormat::Markdown => {
+            let _ = writeln!(renderer.out, "# {}\n", escape_markdown(title));
+            renderer.markdown(nodes, 0)?;
+        }
+        TranscriptFormat::Text => {
+            let _ = writeln!(renderer.out, "{title}\n");
+            renderer.text(nodes, 0)?;
+        }
+        TranscriptFormat::Json => {
+            let transcript = Transcript {
+                version: TRANSCRIPT_VERSION,
+                title: title.to_owned(),
+                messages: nodes.to_vec(),
+            };
+            renderer.out = serde_json::to_string_pretty(&transcript)
+                .map_err(|e| PyValueError::new_err(format!("无法序列化聊天记录: {e:?}")))?;
+        }
+    }
+    Ok(renderer.out)
+}
+
+fn segment_to_dict(py: Python, seg: Segment) -> PyResult<&PyDict> {
+    Ok(match seg {
+        Segment::Text { text } | Segment::Other { text } => dict! {py, type: "Text", text: text},
+        Segment::At { target, display } => dict! {py,
+            type: "At",
+            target: target,
+            display: display,
+        },
+        Segment::AtAll => dict! {py, type: "AtAll"},
+        Segment::Face { index, name } => dict! {py,
+            type: "Face",
+            index: index,
+            name: name,
+        },
+        Segment::Image { url } => dict! {py,
+            type: "Image",
+            url: url,
+            raw: py.None(),
+        },
+        Segment::Reply {
+            seq,
+            sender,
+            time,
+            content,
+        } => dict! {py,
+            type: "Reply",
+            seq: seq,
+            sender: sender,
+            time: datetime_from_ts(py, time)?,
+            content: content,
+        },
+    })
+}
+
+/// 将聊天记录节点转换为与 `download_forward_msg` 相同的格式。
+fn node_to_dict(py: Python, node: TranscriptNode) -> PyResult<&PyDict> {
+    let time = datetime_from_ts(py, node.time)?;
+    Ok(match node.body {
+        TranscriptBody::Content(segments) => {
+            let elements = segments
+                .into_iter()
+                .map(|seg| segment_to_dict(py, seg))
+                .try_collect::<Vec<_>>()?;
+            dict! {py,
+                type: "Message",
+                sender_id: node.sender_id,
+                time: time,
+                sender_name: node.sender_name,
+                content: py_deserialize(py).call1((PyList::new(py, elements),))?,
+            }
+        }
+        TranscriptBody::Forward(nodes) => dict! {py,
+            type: "Forward",
+            sender_id: node.sender_id,
+            time: time,
+            sender_name: node.sender_name,
+            content: nodes
+                .into_iter()
+                .map(|node| node_to_dict(py, node).map(|d| d.into_py(py)))
+                .try_collect::<Vec<PyObject>>()?,
+        },
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (messages, format, *, title=String::from("聊天记录"), images=HashMap::new()))]
+pub fn render_transcript(
+    py: Python,
+    messages: Vec<TranscriptItem>,
+    format: &str,
+    title: String,
+    images: HashMap<String, Vec<u8>>,
+) -> PyResult<String> {
+    let format = TranscriptFormat::try_from(format)?;
+    let nodes = messages
+        .into_iter()
+        .map(|item| item.into_node(py))
+        .try_collect::<Vec<_>>()?;
+    render(py, &nodes, format, &title, &images)
+}
+
+#[pyfunction]
+pub fn load_transcript(py: Python, path: PathBuf) -> PyResult<Vec<PyObject>> {
+    let data = std::fs::read(&path)
+        .map_err(|e| PyIOError::new_err(format!("无法读取聊天记录 {}: {e}", path.display())))?;
+    let transcript: Transcript = serde_json::from_slice(&data)
+        .map_err(|e| PyValueError::new_err(format!("无法解析聊天记录: {e}")))?;
+    if transcript.version > TRANSCRIPT_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "不支持的聊天记录版本: {}",
+            transcript.version
+        )));
+    }
+    transcript
+        .messages
+        .into_iter()
+        .map(|node| node_to_dict(py, node).map(|d| d.into_py(py)))
+        .try_collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_items_render_as_content_nodes() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                r#"
+from types import SimpleNamespace
+
+content = [{"type": "Text", "text": "第二条"}]
+cached = SimpleNamespace(sender=10002, source=SimpleNamespace(time=60), content=content)
+event = SimpleNamespace(
+    sender=10003,
+    sender_info=SimpleNamespace(name="群名片"),
+    source=SimpleNamespace(time=120),
+    content=[{"type": "Text", "text": "第三条"}],
+)
+items = [(10001, 0, [{"type": "Text", "text": "第一条"}]), cached, event]
+"#,
+                None,
+                Some(locals),
+            )
+            .unwrap();
+            let items: Vec<TranscriptItem> = locals.get_item("items").unwrap().extract().unwrap();
+            let nodes = items
+                .into_iter()
+                .map(|item| item.into_node(py))
+                .try_collect::<Vec<_>>()
+                .unwrap();
+            assert_eq!(
+                nodes.iter().map(|node| node.sender_id).collect::<Vec<_>>(),
+                [10001, 10002, 10003]
+            );
+            assert_eq!(nodes[1].sender_name, "10002");
+            assert_eq!(nodes[2].time, 120);
+            let text = render(
+                py,
+                &nodes,
+                TranscriptFormat::Text,
+                "聊天记录",
+                &HashMap::new(),
+            )
+            .unwrap();
+            assert!(text.contains("10001(10001): 第一条"));
+            assert!(text.contains("10002(10002): 第二条"));
+            assert!(text.contains("群名片(10003): 第三条"));
+        });
+    }
+}
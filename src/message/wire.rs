@@ -0,0 +1,651 @@
+//! 消息链的 JSON 传输格式。
+//!
+//! 所有元素都可以被序列化，其中图片、音频与商城表情等封装元素以 base64 编码的 protobuf 数据保存，
+//! 因此可以在其他进程中反序列化后直接发送。
+
+use base64::Engine as _;
+use prost::Message as _;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::*;
+use ricq::msg::elem::{FlashImage, FriendImage, GroupImage, MarketFace, RQElem, Reply};
+use ricq::msg::MessageChain;
+use ricq_core::msg::elem::FingerGuessing;
+use ricq_core::msg::MessageElem as BaseElem;
+use ricq_core::pb::msg as pb;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::convert::py_deserialize;
+use super::elements::*;
+use super::face::{SuperFace, SUPER_FACE_COMPAT_SUFFIX};
+use crate::dict;
+use crate::utils::{datetime_from_ts, dict_item as get};
+
+/// 传输格式的版本，结构发生不兼容的变化时递增。
+pub const WIRE_VERSION: u32 = 1;
+
+/// 无法以文本形式表示的封装元素数据。
+#[derive(Debug, Clone)]
+pub enum Sealed {
+    GroupImage(GroupImage),
+    FriendImage(FriendImage),
+    Audio(pb::Ptt),
+    MarketFace(MarketFace),
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy)]
+enum SealedKind {
+    GroupImage,
+    FriendImage,
+    Audio,
+    MarketFace,
+}
+
+/// 封装元素的传输形式，`data` 为 base64 编码的 protobuf 数据。
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "Sealed")]
+struct SealedRepr {
+    kind: SealedKind,
+    data: String,
+}
+
+/// 借助 RICQ 的打包逻辑获取元素对应的 protobuf 结构。
+fn push_into(push: impl FnOnce(&mut MessageChain)) -> Vec<BaseElem> {
+    let mut chain = MessageChain::default();
+    push(&mut chain);
+    chain.0
+}
+
+impl Sealed {
+    fn encode(&self) -> Result<(SealedKind, Vec<u8>), String> {
+        let find = |elems: Vec<BaseElem>| -> Result<Vec<u8>, String> {
+            elems
+                .into_iter()
+                .find_map(|e| match e {
+                    BaseElem::CustomFace(e) => Some(e.encode_to_vec()),
+                    BaseElem::NotOnlineImage(e) => Some(e.encode_to_vec()),
+                    BaseElem::MarketFace(e) => Some(e.encode_to_vec()),
+                    _ => None,
+                })
+                .ok_or_else(|| "无法封装元素: 未生成对应的 protobuf 数据".to_owned())
+        };
+        Ok(match self {
+            Self::GroupImage(i) => (
+                SealedKind::GroupImage,
+                find(push_into(|c| c.push(i.clone())))?,
+            ),
+            Self::FriendImage(i) => (
+                SealedKind::FriendImage,
+                find(push_into(|c| c.push(i.clone())))?,
+            ),
+            Self::Audio(ptt) => (SealedKind::Audio, ptt.encode_to_vec()),
+            Self::MarketFace(f) => (
+                SealedKind::MarketFace,
+                find(push_into(|c| c.push(f.clone())))?,
+            ),
+        })
+    }
+
+    fn decode(kind: SealedKind, data: &[u8]) -> Result<Self, String> {
+        let elem = match kind {
+            SealedKind::Audio => {
+                return pb::Ptt::decode(data)
+                    .map(Self::Audio)
+                    .map_err(|e| e.to_string())
+            }
+            SealedKind::GroupImage => {
+                BaseElem::CustomFace(pb::CustomFace::decode(data).map_err(|e| e.to_string())?)
+            }
+            SealedKind::FriendImage => BaseElem::NotOnlineImage(
+                pb::NotOnlineImage::decode(data).map_err(|e| e.to_string())?,
+            ),
+            SealedKind::MarketFace => {
+                BaseElem::MarketFace(pb::MarketFace::decode(data).map_err(|e| e.to_string())?)
+            }
+        };
+        match RQElem::from(elem) {
+            RQElem::GroupImage(i) => Ok(Self::GroupImage(i)),
+            RQElem::FriendImage(i) => Ok(Self::FriendImage(i)),
+            RQElem::MarketFace(f) => Ok(Self::MarketFace(f)),
+            other => Err(format!("封装数据类型不匹配: {other:?}")),
+        }
+    }
+
+    fn into_py(self, py: Python, size: (u32, u32)) -> PyObject {
+        match self {
+            Self::GroupImage(inner) => SealedGroupImage { inner }.into_py(py),
+            Self::FriendImage(inner) => SealedFriendImage { inner }.into_py(py),
+            Self::Audio(inner) => SealedAudio { inner }.into_py(py),
+            Self::MarketFace(inner) => {
+                let mut face = SealedMarketFace::new(inner);
+                (face.width, face.height) = size;
+                face.into_py(py)
+            }
+        }
+    }
+
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(i) = obj.extract::<SealedGroupImage>() {
+            return Ok(Self::GroupImage(i.inner));
+        }
+        if let Ok(i) = obj.extract::<SealedFriendImage>() {
+            return Ok(Self::FriendImage(i.inner));
+        }
+        if let Ok(a) = obj.extract::<SealedAudio>() {
+            return Ok(Self::Audio(a.inner));
+        }
+        if let Ok(f) = obj.extract::<SealedMarketFace>() {
+            return Ok(Self::MarketFace(f.inner));
+        }
+        Err(PyTypeError::new_err(format!("无法序列化的封装元素: {obj}")))
+    }
+}
+
+impl Serialize for Sealed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (kind, data) = self.encode().map_err(serde::ser::Error::custom)?;
+        SealedRepr {
+            kind,
+            data: base64::engine::general_purpose::STANDARD.encode(data),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sealed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = SealedRepr::deserialize(deserializer)?;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(repr.data)
+            .map_err(serde::de::Error::custom)?;
+        Self::decode(repr.kind, &data).map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for Sealed {
+    fn schema_name() -> String {
+        SealedRepr::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        SealedRepr::json_schema(gen)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy)]
+pub enum Choice {
+    Rock,
+    Paper,
+    Scissors,
+}
+
+/// 消息元素的传输形式，字段与 Python 侧元素一致。
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum WireElement {
+    Text {
+        text: String,
+    },
+    At {
+        target: i64,
+        display: Option<String>,
+    },
+    AtAll,
+    Face {
+        index: i32,
+        name: String,
+    },
+    SuperFace {
+        index: i32,
+        name: String,
+        large: bool,
+        sticker_type: Option<u32>,
+    },
+    Dice {
+        value: i32,
+    },
+    FingerGuessing {
+        choice: Choice,
+    },
+    Reply {
+        seq: i32,
+        sender: i64,
+        /// 原消息的发送时间戳
+        time: i32,
+        content: String,
+    },
+    Image {
+        url: String,
+        /// 未上传的图片为空
+        raw: Option<Sealed>,
+    },
+    FlashImage {
+        url: String,
+        raw: Option<Sealed>,
+    },
+    Audio {
+        url: String,
+        raw: Option<Sealed>,
+    },
+    MarketFace {
+        raw: Sealed,
+        width: u32,
+        height: u32,
+    },
+    LightApp {
+        content: String,
+    },
+    RichMessage {
+        service_id: i32,
+        content: String,
+    },
+    MusicShare {
+        kind: String,
+        title: String,
+        summary: String,
+        jump_url: String,
+        picture_url: String,
+        music_url: String,
+        brief: String,
+    },
+    Unknown {
+        #[serde(flatten)]
+        data: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl WireElement {
+    /// 转换单个元素，与 `serialize_element` 的规则一致。
+    pub fn from_elem(elem: RQElem) -> Option<Self> {
+        Some(match elem {
+            RQElem::At(a) if a.target == 0 => Self::AtAll,
+            RQElem::At(a) => Self::At {
+                target: a.target,
+                display: Some(a.display),
+            },
+            RQElem::Text(t) => Self::Text { text: t.content },
+            RQElem::Dice(d) => Self::Dice { value: d.value },
+            RQElem::FingerGuessing(f) => Self::FingerGuessing {
+                choice: match f {
+                    FingerGuessing::Rock => Choice::Rock,
+                    FingerGuessing::Paper => Choice::Paper,
+                    FingerGuessing::Scissors => Choice::Scissors,
+                },
+            },
+            RQElem::Face(f) => Self::Face {
+                index: f.index,
+                name: f.name,
+            },
+            RQElem::MarketFace(m) => Self::MarketFace {
+                raw: Sealed::MarketFace(m),
+                width: MARKET_FACE_SIZE,
+                height: MARKET_FACE_SIZE,
+            },
+            RQElem::GroupImage(i) => Self::Image {
+                url: i.url(),
+                raw: Some(Sealed::GroupImage(i)),
+            },
+            RQElem::FriendImage(i) => Self::Image {
+                url: i.url(),
+                raw: Some(Sealed::FriendImage(i)),
+            },
+            RQElem::FlashImage(FlashImage::GroupImage(i)) => Self::FlashImage {
+                url: i.url(),
+                raw: Some(Sealed::GroupImage(i)),
+            },
+            RQElem::FlashImage(FlashImage::FriendImage(i)) => Self::FlashImage {
+                url: i.url(),
+                raw: Some(Sealed::FriendImage(i)),
+            },
+            RQElem::LightApp(app) => Self::LightApp {
+                content: app.content,
+            },
+            RQElem::RichMsg(rich) => Self::RichMessage {
+                service_id: rich.service_id,
+                content: rich.template1,
+            },
+            RQElem::Other(_) => return None,
+            unhandled => {
+                let mut data = serde_json::Map::new();
+                data.insert("raw".into(), format!("{unhandled:?}").into());
+                Self::Unknown { data }
+            }
+        })
+    }
+
+    pub fn audio(url: String, ptt: &pb::Ptt) -> Self {
+        Self::Audio {
+            url,
+            raw: Some(Sealed::Audio(ptt.clone())),
+        }
+    }
+
+    /// 转换为 Python 侧使用的元素字典。
+    pub fn into_py_dict(self, py: Python) -> PyResult<&PyDict> {
+        let sealed = |raw: Option<Sealed>| match raw {
+            Some(raw) => raw.into_py(py, (0, 0)),
+            None => py.None(),
+        };
+        Ok(match self {
+            Self::Text { text } => dict! {py, type: "Text", text: text},
+            Self::At { target, display } => dict! {py,
+                type: "At",
+                target: target,
+                display: display,
+            },
+            Self::AtAll => dict! {py, type: "AtAll"},
+            Self::Face { index, name } => dict! {py, type: "Face", index: index, name: name},
+            Self::SuperFace {
+                index,
+                name,
+                large,
+                sticker_type,
+            } => dict! {py,
+                type: "SuperFace",
+                index: index,
+                name: name,
+                large: large,
+                sticker_type: sticker_type,
+            },
+            Self::Dice { value } => dict! {py, type: "Dice", value: value},
+            Self::FingerGuessing { choice } => dict! {py,
+                type: "FingerGuessing",
+                choice: format!("{choice:?}"),
+            },
+            Self::Reply {
+                seq,
+                sender,
+                time,
+                content,
+            } => dict! {py,
+                type: "Reply",
+                seq: seq,
+                sender: sender,
+                time: datetime_from_ts(py, time)?,
+                content: content,
+            },
+            Self::Image { url, raw } => dict! {py, type: "Image", url: url, raw: sealed(raw)},
+            Self::FlashImage { url, raw } => dict! {py,
+                type: "FlashImage",
+                url: url,
+                raw: sealed(raw),
+            },
+            Self::Audio { url, raw } => dict! {py, type: "Audio", url: url, raw: sealed(raw)},
+            Self::MarketFace { raw, width, height } => dict! {py,
+                type: "MarketFace",
+                raw: raw.into_py(py, (width, height)),
+            },
+            Self::LightApp { content } => dict! {py, type: "LightApp", content: content},
+            Self::RichMessage {
+                service_id,
+                content,
+            } => dict! {py,
+                type: "RichMessage",
+                service_id: service_id,
+                content: content,
+            },
+            Self::MusicShare {
+                kind,
+                title,
+                summary,
+                jump_url,
+                picture_url,
+                music_url,
+                brief,
+            } => dict! {py,
+                type: "MusicShare",
+                kind: kind,
+                title: title,
+                summary: summary,
+                jump_url: jump_url,
+                picture_url: picture_url,
+                music_url: music_url,
+                brief: brief,
+            },
+            Self::Unknown { data } => {
+                let res = dict! {py, type: "Unknown"};
+                for (k, v) in data {
+                    res.set_item(k, pythonize::pythonize(py, &v)?)?;
+                }
+                res
+            }
+        })
+    }
+
+    /// 从 Python 侧的元素字典转换。
+    pub fn from_py_dict(data: &PyDict) -> PyResult<Self> {
+        let kind: &str = get(data, "type")?;
+        let raw = || -> PyResult<Option<Sealed>> {
+            match data.get_item("raw") {
+                Some(raw) if !raw.is_none() => Sealed::from_py(raw).map(Some),
+                _ => Ok(None),
+            }
+        };
+        Ok(match kind {
+            "Text" => Self::Text {
+                text: get(data, "text")?,
+            },
+            "At" => Self::At {
+                target: get(data, "target")?,
+                display: data.get_item("display").map(|d| d.extract()).transpose()?.flatten(),
+            },
+            "AtAll" => Self::AtAll,
+            "Face" => Self::Face {
+                index: get(data, "index")?,
+                name: get(data, "name")?,
+            },
+            "SuperFace" => Self::SuperFace {
+                index: get(data, "index")?,
+                name: get(data, "name")?,
+                large: get(data, "large")?,
+                sticker_type: get(data, "sticker_type")?,
+            },
+            "Dice" => Self::Dice {
+                value: get(data, "value")?,
+            },
+            "FingerGuessing" => Self::FingerGuessing {
+                choice: match get::<&str>(data, "choice")? {
+                    "Rock" => Choice::Rock,
+                    "Paper" => Choice::Paper,
+                    "Scissors" => Choice::Scissors,
+                    other => return Err(PyValueError::new_err(format!("无效的猜拳选项: {other}"))),
+                },
+            },
+            "Reply" => {
+                let time: &PyAny = get(data, "time")?;
+                let time = match time.extract::<i32>() {
+                    Ok(ts) => ts,
+                    Err(_) => time.call_method0("timestamp")?.extract::<f64>()? as i32,
+                };
+                Self::Reply {
+                    seq: get(data, "seq")?,
+                    sender: get(data, "sender")?,
+                    time,
+                    content: get(data, "content")?,
+                }
+            }
+            "Image" => Self::Image {
+                url: get(data, "url")?,
+                raw: raw()?,
+            },
+            "FlashImage" => Self::FlashImage {
+                url: get(data, "url")?,
+                raw: raw()?,
+            },
+            "Audio" => Self::Audio {
+                url: get(data, "url")?,
+                raw: raw()?,
+            },
+            "MarketFace" => {
+                let face: SealedMarketFace = get(data, "raw")?;
+                Self::MarketFace {
+                    width: face.width,
+                    height: face.height,
+                    raw: Sealed::MarketFace(face.inner),
+                }
+            }
+            "LightApp" => Self::LightApp {
+                content: get(data, "content")?,
+            },
+            "RichMessage" => Self::RichMessage {
+                service_id: get(data, "service_id")?,
+                content: get(data, "content")?,
+            },
+            "MusicShare" => Self::MusicShare {
+                kind: get(data, "kind")?,
+                title: get(data, "title")?,
+                summary: get(data, "summary")?,
+                jump_url: get(data, "jump_url")?,
+                picture_url: get(data, "picture_url")?,
+                music_url: get(data, "music_url")?,
+                brief: get(data, "brief")?,
+            },
+            "Unknown" => {
+                let mut res = serde_json::Map::new();
+                for (k, v) in data.iter() {
+                    let k: String = k.extract()?;
+                    if k != "type" {
+                        let v: serde_json::Value = pythonize::depythonize(v)
+                            .map_err(|e| PyValueError::new_err(format!("无法序列化字段 {k}: {e}")))?;
+                        res.insert(k, v);
+                    }
+                }
+                Self::Unknown { data: res }
+            }
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "无法序列化的元素类型: {other}"
+                )))
+            }
+        })
+    }
+}
+
+/// 消息链的传输形式。
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct WireChain(pub Vec<WireElement>);
+
+impl From<MessageChain> for WireChain {
+    fn from(chain: MessageChain) -> Self {
+        let mut res = Vec::new();
+        let mut skip_compat = false;
+        for e in chain.0 {
+            let after_large_face = std::mem::take(&mut skip_compat);
+            match e {
+                BaseElem::SrcMsg(reply) => {
+                    let reply: Reply = reply.into();
+                    res.push(WireElement::Reply {
+                        seq: reply.reply_seq,
+                        sender: reply.sender,
+                        time: reply.time,
+                        content: reply.elements.to_string(),
+                    });
+                }
+                BaseElem::AnonGroupMsg(_) => {} // Anonymous information, TODO
+                BaseElem::CommonElem(common) => match SuperFace::decode(&common) {
+                    Some(face) => {
+                        skip_compat = face.large;
+                        res.push(WireElement::SuperFace {
+                            index: face.index,
+                            name: face.name,
+                            large: face.large,
+                            sticker_type: face.sticker_type,
+                        });
+                    }
+                    None => res.extend(WireElement::from_elem(RQElem::from(
+                        BaseElem::CommonElem(common),
+                    ))),
+                },
+                BaseElem::MarketFace(face) => {
+                    let (width, height) = (face.image_width(), face.image_height());
+                    if let RQElem::MarketFace(m) = RQElem::from(BaseElem::MarketFace(face)) {
+                        let (width, height) = if width != 0 && height != 0 {
+                            (width, height)
+                        } else {
+                            (MARKET_FACE_SIZE, MARKET_FACE_SIZE)
+                        };
+                        res.push(WireElement::MarketFace {
+                            raw: Sealed::MarketFace(m),
+                            width,
+                            height,
+                        });
+                    }
+                }
+                // 大号超级表情附带的兼容文本
+                BaseElem::Text(ref text)
+                    if after_large_face && text.str().ends_with(SUPER_FACE_COMPAT_SUFFIX) => {}
+                elem => res.extend(WireElement::from_elem(RQElem::from(elem))),
+            }
+        }
+        Self(res)
+    }
+}
+
+impl WireChain {
+    /// 消息链中的纯文本内容。
+    pub fn plain_text(&self) -> String {
+        self.0
+            .iter()
+            .filter_map(|elem| match elem {
+                WireElement::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn into_py_list(self, py: Python) -> PyResult<&PyList> {
+        let res = PyList::empty(py);
+        for elem in self.0 {
+            res.append(elem.into_py_dict(py)?)?;
+        }
+        Ok(res)
+    }
+
+    /// 转换为 Python 侧的消息链。
+    pub fn into_py_chain(self, py: Python) -> PyResult<PyObject> {
+        Ok(py_deserialize(py).call1((self.into_py_list(py)?,))?.into_py(py))
+    }
+
+    pub fn from_py_list(elements: &PyAny) -> PyResult<Self> {
+        elements
+            .iter()?
+            .map(|e| WireElement::from_py_dict(e?.downcast()?))
+            .try_collect()
+            .map(Self)
+    }
+}
+
+/// 带版本号的消息链。
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ChainEnvelope {
+    pub version: u32,
+    pub elements: WireChain,
+}
+
+pub fn check_version(version: u32) -> PyResult<()> {
+    if version > WIRE_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "不支持的传输格式版本: {version}，当前版本为 {WIRE_VERSION}"
+        )));
+    }
+    Ok(())
+}
+
+#[pyfunction]
+pub fn elements_to_json(elements: &PyAny) -> PyResult<String> {
+    serde_json::to_string(&ChainEnvelope {
+        version: WIRE_VERSION,
+        elements: WireChain::from_py_list(elements)?,
+    })
+    .map_err(|e| PyValueError::new_err(format!("无法序列化消息链: {e}")))
+}
+
+#[pyfunction]
+pub fn elements_from_json<'py>(py: Python<'py>, data: &str) -> PyResult<&'py PyList> {
+    let envelope: ChainEnvelope = serde_json::from_str(data)
+        .map_err(|e| PyValueError::new_err(format!("无法解析消息链: {e}")))?;
+    check_version(envelope.version)?;
+    envelope.elements.into_py_list(py)
+}
@@ -1,6 +1,7 @@
 use futures_util::Future;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
+use pyo3::types::{PyBytes, PyDict};
 
 // use pyo3::types::*;
 
@@ -164,6 +165,13 @@ pub fn timedelta_from_secs(py: Python<'_>, delta: impl IntoPy<PyObject>) -> PyRe
     _timedelta_from_secs(py).call((), Some(dict!(py, seconds: delta.into_py(py))))
 }
 
+/// 从元素或事件字典中取出并转换字段，字段缺失时报错。
+pub fn dict_item<'py, T: FromPyObject<'py>>(data: &'py PyDict, key: &str) -> PyResult<T> {
+    data.get_item(key)
+        .ok_or_else(|| PyValueError::new_err(format!("字典缺少字段 {key}: {data}")))?
+        .extract()
+}
+
 static_py_fn!(partial, __PARTIAL_CELL, "functools", ["partial"]);
 
 static_py_fn!(
@@ -196,14 +204,28 @@ static_py_fn!(
     ["GroupPermission"]
 );
 
-pub fn to_py_permission(perm: ricq_core::structs::GroupMemberPermission) -> PyObject {
+/// 群成员权限的名称，与 Python 侧 `GroupPermission` 的成员名一致。
+pub fn permission_name(perm: &ricq_core::structs::GroupMemberPermission) -> &'static str {
     use ricq_core::structs::GroupMemberPermission as Perm;
-    let perm_str = match perm {
+    match perm {
         Perm::Owner => "Owner",
         Perm::Administrator => "Admin",
         Perm::Member => "Member",
-    };
-    py_use(|py| _to_py_perm(py).call1((perm_str,)).unwrap().into_py(py))
+    }
+}
+
+pub fn to_py_permission(perm: ricq_core::structs::GroupMemberPermission) -> PyObject {
+    py_use(|py| {
+        _to_py_perm(py)
+            .call1((permission_name(&perm),))
+            .unwrap()
+            .into_py(py)
+    })
+}
+
+/// 按名称转换为 Python 侧的 `GroupPermission`。
+pub fn py_permission_from_name<'py>(py: Python<'py>, name: &str) -> PyResult<&'py PyAny> {
+    _to_py_perm(py).call1((name,))
 }
 
 #[macro_export]